@@ -3,16 +3,32 @@ use std::{
     fs::File,
     io::{BufReader, Read},
     path::Path,
+    str::FromStr,
 };
 
 use anyhow::{Context, Result};
 
+pub mod profiling;
+pub mod progress;
+#[cfg(feature = "serde")]
+pub mod serialization;
+
 pub fn get_arg(nth: usize) -> Result<String> {
     env::args()
         .nth(nth)
         .with_context(|| format!("trying to read nth ({}) argument", nth))
 }
 
+/// Returns value passed after `name` flag (e.g. `--dump path`), if present.
+pub fn get_option(name: &str) -> Option<String> {
+    env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+/// Checks if `name` flag (e.g. `--verbose`) was passed anywhere in arguments.
+pub fn has_flag(name: &str) -> bool {
+    env::args().any(|arg| arg == name)
+}
+
 fn read_file(path_str: &str) -> Result<BufReader<File>> {
     let path = Path::new(path_str);
     let file = File::open(path).with_context(|| format!("trying to open {}", path.display()))?;
@@ -28,3 +44,8 @@ pub fn read_file_to_string(path_str: &str) -> Result<String> {
 
     Ok(s)
 }
+
+/// Reads and parses problem from text input file at `path_str`.
+pub fn read_problem<T: FromStr<Err = anyhow::Error>>(path_str: &str) -> Result<T> {
+    read_file_to_string(path_str)?.parse()
+}
@@ -0,0 +1,190 @@
+//! Heap usage profiling, based on a global allocator which counts allocations.
+//!
+//! Day binaries install [`CountingAllocator`] when built with `profiling`
+//! feature, and wrap computation of every part in [`profile`], which reports
+//! its peak heap usage and allocation counts when `--profile` flag is passed.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::has_flag;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+static CURRENT_BYTES: AtomicU64 = AtomicU64::new(0);
+static PEAK_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Wrapper around [`System`] allocator, which keeps track of number and size of
+/// allocations, as well as of peak heap usage.
+pub struct CountingAllocator;
+
+fn record_allocation(size: u64) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+    let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+}
+
+fn record_deallocation(size: u64) {
+    CURRENT_BYTES.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size() as u64);
+        }
+
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size() as u64);
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_deallocation(layout.size() as u64);
+    }
+
+    /// Counts reallocation as deallocation of the old block followed by
+    /// allocation of the new one, as that is its worst case.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_deallocation(layout.size() as u64);
+            record_allocation(new_size as u64);
+        }
+
+        new_ptr
+    }
+}
+
+/// Heap usage of a measured computation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AllocationStats {
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+    /// Peak heap usage above the level from before the computation
+    pub peak_bytes: u64,
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+impl Display for AllocationStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peak {}, {} allocations, {} allocated",
+            format_bytes(self.peak_bytes),
+            self.allocations,
+            format_bytes(self.allocated_bytes)
+        )
+    }
+}
+
+/// Runs `f`, measuring its heap usage. Peak usage is tracked globally, so
+/// measured computations should not overlap.
+///
+/// Returns `None` for stats when [`CountingAllocator`] is not installed.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocationStats>) {
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated_bytes_before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let current_bytes_before = CURRENT_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(current_bytes_before, Ordering::Relaxed);
+
+    let value = f();
+
+    // Every Rust program allocates before reaching `main`, so no allocations
+    // at all means allocator is not installed
+    if allocations_before == 0 {
+        return (value, None);
+    }
+
+    let stats = AllocationStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations_before,
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes_before,
+        peak_bytes: PEAK_BYTES
+            .load(Ordering::Relaxed)
+            .saturating_sub(current_bytes_before),
+    };
+
+    (value, Some(stats))
+}
+
+/// Runs `f` and, when `--profile` flag was passed, prints its heap usage to
+/// stderr under `label`.
+pub fn profile<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    if !has_flag("--profile") {
+        return f();
+    }
+
+    let (value, stats) = measure(f);
+
+    match stats {
+        Some(stats) => eprintln!("{} profile: {}", label, stats),
+        None => eprintln!(
+            "{} profile: counting allocator not installed, build with `--features profiling`",
+            label
+        ),
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_measure() {
+        let (v, stats) = measure(|| {
+            let mut v: Vec<u64> = Vec::with_capacity(1024);
+            v.push(1);
+            let w = vec![0u8; 4096];
+            drop(w);
+
+            v
+        });
+        let stats = stats.unwrap();
+
+        assert_eq!(v, vec![1]);
+        assert!(stats.allocations >= 2);
+        assert!(stats.allocated_bytes >= 8 * 1024 + 4096);
+        assert!(stats.peak_bytes >= 8 * 1024 + 4096);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
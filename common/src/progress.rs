@@ -0,0 +1,278 @@
+//! Cooperative progress reporting and cancellation for long-running solvers.
+//!
+//! Solvers advance a shared [`Progress`] as they go and poll
+//! [`Progress::should_stop`], returning best answer found so far (wrapped in
+//! [`Outcome::Partial`]) once the time budget is exhausted or the user pressed
+//! Ctrl-C.
+
+use std::{
+    fmt::Display,
+    io::{self, IsTerminal, Write},
+    process,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+
+use crate::get_option;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+const RENDER_INTERVAL: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 30;
+
+/// Installs Ctrl-C handler which asks all [`Progress`]es to stop. Pressing
+/// Ctrl-C a second time terminates the process right away.
+pub fn cancel_on_ctrl_c() -> Result<()> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            process::exit(130);
+        }
+    })
+    .context("trying to install Ctrl-C handler")
+}
+
+/// Reads time budget (in seconds) passed with `--budget` option.
+pub fn get_budget() -> Result<Option<Duration>> {
+    get_option("--budget")
+        .map(|secs| {
+            let secs: f64 = secs
+                .parse()
+                .with_context(|| format!("couldn't parse --budget seconds from '{}'", secs))?;
+
+            Ok(Duration::from_secs_f64(secs))
+        })
+        .transpose()
+}
+
+/// Result of a computation that might have been stopped before completion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome<T> {
+    Complete(T),
+    /// Best result found before computation was stopped
+    Partial(T),
+}
+
+impl<T> Outcome<T> {
+    pub fn is_partial(&self) -> bool {
+        matches!(self, Outcome::Partial(_))
+    }
+
+    pub fn into_inner(self) -> T {
+        match self {
+            Outcome::Complete(v) | Outcome::Partial(v) => v,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        match self {
+            Outcome::Complete(v) => Outcome::Complete(f(v)),
+            Outcome::Partial(v) => Outcome::Partial(f(v)),
+        }
+    }
+}
+
+impl<T: Display> Display for Outcome<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Complete(v) => write!(f, "{}", v),
+            Outcome::Partial(v) => write!(f, "{} (partial)", v),
+        }
+    }
+}
+
+/// Shared progress of a computation consisting of `total` steps. Can be
+/// advanced from multiple threads.
+#[derive(Debug)]
+pub struct Progress {
+    label: String,
+    total: u64,
+    done: AtomicU64,
+    start: Instant,
+    budget: Option<Duration>,
+    stopped: AtomicBool,
+    visible: bool,
+    last_render: Mutex<Option<Instant>>,
+}
+
+impl Progress {
+    /// Creates progress which is rendered to stderr, when it is a terminal.
+    pub fn new(label: &str, total: u64) -> Self {
+        Self {
+            label: label.to_string(),
+            total,
+            done: AtomicU64::new(0),
+            start: Instant::now(),
+            budget: None,
+            stopped: AtomicBool::new(false),
+            visible: io::stderr().is_terminal(),
+            last_render: Mutex::new(None),
+        }
+    }
+
+    /// Creates progress which is never rendered.
+    pub fn hidden(total: u64) -> Self {
+        Self {
+            visible: false,
+            ..Self::new("", total)
+        }
+    }
+
+    /// Sets time budget, after which [`Progress::should_stop`] returns `true`.
+    pub fn with_budget(mut self, budget: Option<Duration>) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Marks `steps` more steps as done.
+    pub fn advance(&self, steps: u64) {
+        self.done.fetch_add(steps, Ordering::Relaxed);
+        self.render(false);
+    }
+
+    /// Asks computation to stop at the next poll.
+    pub fn cancel(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks if computation should stop, either because it was cancelled, ran
+    /// out of time budget, or the user pressed Ctrl-C.
+    pub fn should_stop(&self) -> bool {
+        if self.stopped.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let out_of_budget = self
+            .budget
+            .is_some_and(|budget| self.start.elapsed() >= budget);
+
+        if out_of_budget || INTERRUPTED.load(Ordering::Relaxed) {
+            self.cancel();
+            return true;
+        }
+
+        false
+    }
+
+    /// Checks if computation was stopped before completion. Meant to be called
+    /// once the computation returns.
+    pub fn was_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Wraps `value` in [`Outcome`] according to whether computation was
+    /// stopped, and clears rendered progress bar.
+    pub fn finish<T>(&self, value: T) -> Outcome<T> {
+        self.render(true);
+        if self.visible {
+            eprintln!();
+        }
+
+        if self.was_stopped() {
+            Outcome::Partial(value)
+        } else {
+            Outcome::Complete(value)
+        }
+    }
+
+    /// Estimates time left, based on average pace so far.
+    pub fn eta(&self) -> Option<Duration> {
+        let done = self.done.load(Ordering::Relaxed).min(self.total);
+        if done == 0 {
+            return None;
+        }
+
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let left = (self.total - done) as f64 * elapsed / done as f64;
+
+        Some(Duration::from_secs_f64(left))
+    }
+
+    /// Renders progress bar line, e.g. `part 2 [#####-----] 50.0% ETA 3.2s`.
+    pub fn render_line(&self) -> String {
+        let done = self.done.load(Ordering::Relaxed).min(self.total);
+        let ratio = if self.total == 0 {
+            1.0
+        } else {
+            done as f64 / self.total as f64
+        };
+        let filled = (ratio * BAR_WIDTH as f64) as usize;
+        let eta = match self.eta() {
+            Some(eta) => format!("{:.1}s", eta.as_secs_f64()),
+            None => "?".to_string(),
+        };
+
+        format!(
+            "{} [{}{}] {:.1}% ETA {}",
+            self.label,
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            ratio * 100.0,
+            eta
+        )
+    }
+
+    fn render(&self, force: bool) {
+        if !self.visible {
+            return;
+        }
+
+        // Skip rendering if other thread is already at it
+        let Ok(mut last_render) = self.last_render.try_lock() else {
+            return;
+        };
+
+        let now = Instant::now();
+        if !force && last_render.is_some_and(|t| now - t < RENDER_INTERVAL) {
+            return;
+        }
+        *last_render = Some(now);
+
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r{}", self.render_line());
+        let _ = stderr.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_line() {
+        let progress = Progress::hidden(4);
+
+        assert_eq!(
+            progress.render_line(),
+            " [------------------------------] 0.0% ETA ?"
+        );
+
+        progress.advance(2);
+
+        assert!(progress
+            .render_line()
+            .starts_with(" [###############---------------] 50.0% ETA "));
+    }
+
+    #[test]
+    fn test_budget_stops_computation() {
+        let progress = Progress::hidden(100).with_budget(Some(Duration::ZERO));
+
+        assert!(progress.should_stop());
+        assert_eq!(progress.finish(42), Outcome::Partial(42));
+    }
+
+    #[test]
+    fn test_complete_outcome() {
+        let progress = Progress::hidden(1);
+        progress.advance(1);
+
+        assert!(!progress.should_stop());
+        assert_eq!(progress.finish(7).to_string(), "7");
+        assert_eq!(Outcome::Partial(7).to_string(), "7 (partial)");
+    }
+}
@@ -0,0 +1,151 @@
+//! Dumping and loading of parsed problems and intermediate results, so that
+//! expensive preprocessing can be cached, inspected in other tools, or
+//! hand-crafted without going through the text input format.
+//!
+//! Format is picked based on file extension: `.json` for JSON and `.msgpack`
+//! (or `.mp`) for MessagePack.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{get_option, read_file};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    MessagePack,
+}
+
+impl Format {
+    /// Determines format from extension of `path_str`, if it is a known one.
+    pub fn from_path(path_str: &str) -> Option<Format> {
+        match Path::new(path_str).extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "msgpack" | "mp" => Some(Format::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+fn format_of(path_str: &str) -> Result<Format> {
+    let Some(format) = Format::from_path(path_str) else {
+        bail!(
+            "unknown serialization format of '{}', expected .json or .msgpack",
+            path_str
+        );
+    };
+
+    Ok(format)
+}
+
+/// Serializes `value` into file at `path_str`.
+pub fn dump<T: Serialize>(path_str: &str, value: &T) -> Result<()> {
+    let format = format_of(path_str)?;
+    let file = File::create(path_str).with_context(|| format!("trying to create {}", path_str))?;
+    let mut writer = BufWriter::new(file);
+
+    match format {
+        Format::Json => serde_json::to_writer(&mut writer, value)?,
+        Format::MessagePack => rmp_serde::encode::write_named(&mut writer, value)?,
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Deserializes value from file at `path_str`.
+pub fn load<T: DeserializeOwned>(path_str: &str) -> Result<T> {
+    let format = format_of(path_str)?;
+    let reader = read_file(path_str)?;
+
+    let value = match format {
+        Format::Json => serde_json::from_reader(reader)
+            .with_context(|| format!("trying to deserialize JSON from {}", path_str))?,
+        Format::MessagePack => rmp_serde::from_read(reader)
+            .with_context(|| format!("trying to deserialize MessagePack from {}", path_str))?,
+    };
+
+    Ok(value)
+}
+
+/// Reads problem either from its serialized form, when `path_str` has one of
+/// the known extensions, or by parsing it from the text input. Parsed problem
+/// is dumped to path given with `--dump-problem` option, if present.
+pub fn read_problem<T>(path_str: &str) -> Result<T>
+where
+    T: FromStr<Err = anyhow::Error> + Serialize + DeserializeOwned,
+{
+    let problem = match Format::from_path(path_str) {
+        Some(_) => load(path_str)?,
+        None => crate::read_problem(path_str)?,
+    };
+
+    if let Some(path) = get_option("--dump-problem") {
+        dump(&path, &problem)?;
+    }
+
+    Ok(problem)
+}
+
+/// Loads value from path given with `--load-<name>` option, or computes it.
+/// Either way, dumps it to path given with `--dump-<name>` option, if present.
+pub fn cached<T, F>(name: &str, compute: F) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> T,
+{
+    let value = match get_option(&format!("--load-{}", name)) {
+        Some(path) => load(&path)?,
+        None => compute(),
+    };
+
+    if let Some(path) = get_option(&format!("--dump-{}", name)) {
+        dump(&path, &value)?;
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("dirs.json"), Some(Format::Json));
+        assert_eq!(
+            Format::from_path("./a/b.msgpack"),
+            Some(Format::MessagePack)
+        );
+        assert_eq!(Format::from_path("input.txt"), None);
+        assert_eq!(Format::from_path("input"), None);
+    }
+
+    #[test]
+    fn test_dump_and_load() {
+        let value = vec![vec![Some(1u32), None], vec![None, Some(0)]];
+
+        for extension in ["json", "msgpack"] {
+            let path = std::env::temp_dir().join(format!(
+                "common_serialization_test_{}.{}",
+                std::process::id(),
+                extension
+            ));
+            let path = path.to_str().unwrap();
+
+            dump(path, &value).unwrap();
+            let loaded: Vec<Vec<Option<u32>>> = load(path).unwrap();
+            std::fs::remove_file(path).unwrap();
+
+            assert_eq!(loaded, value);
+        }
+    }
+}
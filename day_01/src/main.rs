@@ -1,9 +1,27 @@
-use std::str::FromStr;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    str::FromStr,
+};
 
-use anyhow::Context;
-use common::{get_arg, read_file_to_string};
+use anyhow::{anyhow, Context};
+#[cfg(not(feature = "serde"))]
+use common::read_problem;
+#[cfg(feature = "serde")]
+use common::serialization::read_problem;
+use common::{get_arg, get_option, has_flag, profiling::profile};
+
+mod partition;
+mod report;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Problem {
     elven_inventories: Vec<Vec<u64>>,
 }
@@ -38,18 +56,142 @@ fn sum_inventories(inventories: &[Vec<u64>]) -> Vec<u64> {
 }
 
 fn find_top_n(inventories: &[Vec<u64>], num: usize) -> Vec<u64> {
-    let mut inventory_sums = sum_inventories(&inventories);
+    let mut inventory_sums = sum_inventories(inventories);
     inventory_sums.sort();
 
     inventory_sums.into_iter().rev().take(num).collect()
 }
 
+/// Finds `num` largest inventory totals in blank-line-separated groups read
+/// from `reader`, without keeping whole input in memory. Returns totals in
+/// descending order, each along with index of the elf carrying it (on ties,
+/// elf which comes first wins).
+fn find_top_n_streaming(reader: impl Read, num: usize) -> Result<Vec<(usize, u64)>, anyhow::Error> {
+    // Min-heap of the best totals so far, so the worst of them is at the top.
+    // Among equal totals later elves are considered smaller.
+    let mut top: BinaryHeap<Reverse<(u64, Reverse<usize>)>> = BinaryHeap::with_capacity(num + 1);
+    let mut push = |elf: usize, total: u64| {
+        top.push(Reverse((total, Reverse(elf))));
+        if top.len() > num {
+            top.pop();
+        }
+    };
+
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut elf = 0;
+    let mut total: Option<u64> = None;
+
+    loop {
+        line.clear();
+        line_number += 1;
+        let read = reader
+            .read_line(&mut line)
+            .with_context(|| format!("couldn't read line {}", line_number))?;
+        let trimmed = line.trim_end();
+
+        if trimmed.is_empty() {
+            if let Some(total) = total.take() {
+                push(elf, total);
+                elf += 1;
+            }
+        } else {
+            let calories = trimmed.parse::<u64>().with_context(|| {
+                format!("couldn't parse u64 from line {}: {}", line_number, trimmed)
+            })?;
+            let sum = total
+                .unwrap_or(0)
+                .checked_add(calories)
+                .ok_or_else(|| anyhow!("total calories overflow u64 at line {}", line_number))?;
+            total = Some(sum);
+        }
+
+        if read == 0 {
+            break;
+        }
+    }
+
+    let mut top: Vec<_> = top
+        .into_iter()
+        .map(|Reverse((total, Reverse(elf)))| (elf, total))
+        .collect();
+    top.sort_by_key(|&(elf, total)| (Reverse(total), elf));
+
+    Ok(top)
+}
+
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
-    let input_string = read_file_to_string(&input_file_path)?;
-    let Problem { elven_inventories } = input_string.parse()?;
 
-    let top_inventories = find_top_n(&elven_inventories, 3);
+    if has_flag("--stream") {
+        let file = File::open(&input_file_path)
+            .with_context(|| format!("couldn't open file: {}", input_file_path))?;
+        let top_elves = profile("parts 1 and 2", || find_top_n_streaming(file, 3))?;
+
+        println!(
+            "Part 1 solution: {}",
+            top_elves.first().map_or(0, |&(_, total)| total)
+        );
+        println!(
+            "Part 2 solution: {}",
+            top_elves.iter().map(|(_, total)| total).sum::<u64>()
+        );
+        println!(
+            "Top elves: {}",
+            top_elves
+                .iter()
+                .map(|(elf, _)| elf.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        return Ok(());
+    }
+
+    let Problem { elven_inventories } = read_problem(&input_file_path)?;
+
+    if let Some(teams_count) = get_option("--teams") {
+        let teams_count: usize = teams_count
+            .parse()
+            .with_context(|| format!("couldn't parse --teams from '{}'", teams_count))?;
+        let totals = sum_inventories(&elven_inventories);
+        let exact = if has_flag("--exact") {
+            true
+        } else if has_flag("--heuristic") {
+            false
+        } else {
+            totals.len() <= partition::EXACT_MAX_ELVES
+        };
+
+        let partition = profile("partitioning", || {
+            if exact {
+                partition::partition_exact(&totals, teams_count)
+            } else {
+                partition::partition_heuristic(&totals, teams_count)
+            }
+        })?;
+        print!("{}", partition);
+
+        return Ok(());
+    }
+
+    if has_flag("--report") {
+        let report = report::Report::new(&elven_inventories)?;
+
+        if has_flag("--json") {
+            #[cfg(feature = "serde")]
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            #[cfg(not(feature = "serde"))]
+            anyhow::bail!("JSON report requires `serde` feature");
+        } else {
+            print!("{}", report);
+        }
+
+        return Ok(());
+    }
+
+    let top_inventories = profile("parts 1 and 2", || find_top_n(&elven_inventories, 3));
 
     println!("Part 1 solution: {}", top_inventories[0]);
     println!("Part 2 solution: {}", top_inventories.iter().sum::<u64>());
@@ -106,4 +248,54 @@ mod tests {
 
         assert_eq!(find_top_n(&elven_inventories, 3).iter().sum::<u64>(), 45000)
     }
+
+    #[test]
+    fn test_find_top_n_streaming() {
+        let top = find_top_n_streaming(TEST_INPUT.as_bytes(), 3).unwrap();
+
+        assert_eq!(top, vec![(3, 24000), (2, 11000), (4, 10000)]);
+    }
+
+    #[test]
+    fn test_find_top_n_streaming_matches_find_top_n() {
+        let Problem { elven_inventories } = TEST_INPUT.parse().unwrap();
+        // Trailing and repeated blank lines don't produce empty inventories
+        let input = format!("\n{}\n\n\n", TEST_INPUT.replace("\n\n", "\n\n\n"));
+
+        for num in 0..7 {
+            let top = find_top_n_streaming(input.as_bytes(), num).unwrap();
+
+            assert_eq!(
+                top.into_iter().map(|(_, total)| total).collect::<Vec<_>>(),
+                find_top_n(&elven_inventories, num)
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_top_n_streaming_ties() {
+        let top = find_top_n_streaming("5\n\n3\n2\n\n1\n\n4\n1".as_bytes(), 2).unwrap();
+
+        assert_eq!(top, vec![(0, 5), (1, 5)]);
+    }
+
+    #[test]
+    fn test_find_top_n_streaming_invalid_line() {
+        let err = find_top_n_streaming("1\n\nabc\n".as_bytes(), 1).unwrap_err();
+
+        assert_eq!(err.to_string(), "couldn't parse u64 from line 3: abc");
+    }
+
+    #[test]
+    fn test_find_top_n_streaming_overflow() {
+        let input = format!("1\n\n{}\n1\n", u64::MAX);
+        let err = find_top_n_streaming(input.as_bytes(), 1).unwrap_err();
+
+        assert_eq!(err.to_string(), "total calories overflow u64 at line 4");
+    }
+
+    #[test]
+    fn test_find_top_n_streaming_empty() {
+        assert_eq!(find_top_n_streaming("".as_bytes(), 3).unwrap(), vec![]);
+    }
 }
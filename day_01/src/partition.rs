@@ -0,0 +1,338 @@
+//! Splitting elves into teams carrying as even amounts of calories as possible
+//! (multiway number partitioning).
+
+use std::fmt::Display;
+
+use anyhow::bail;
+
+/// Largest number of elves the exact solver is willing to take on
+pub const EXACT_MAX_ELVES: usize = 20;
+
+/// Elves split into teams, each team listing indices of its elves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Partition {
+    pub teams: Vec<Vec<usize>>,
+    pub sums: Vec<u64>,
+}
+
+impl Partition {
+    fn new(totals: &[u64], assignment: &[usize], teams_count: usize) -> Self {
+        let mut teams = vec![vec![]; teams_count];
+        let mut sums = vec![0; teams_count];
+
+        for (elf, &team) in assignment.iter().enumerate() {
+            teams[team].push(elf);
+            sums[team] += totals[elf];
+        }
+
+        Self { teams, sums }
+    }
+
+    /// Difference between sums of the heaviest and the lightest team.
+    pub fn spread(&self) -> u64 {
+        spread(&self.sums)
+    }
+}
+
+impl Display for Partition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (team, sum)) in self.teams.iter().zip(&self.sums).enumerate() {
+            let elves = team
+                .iter()
+                .map(|elf| elf.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            writeln!(f, "Team {} ({}): {}", i + 1, sum, elves)?;
+        }
+
+        writeln!(f, "Spread: {}", self.spread())
+    }
+}
+
+fn spread(sums: &[u64]) -> u64 {
+    let max = sums.iter().max().unwrap_or(&0);
+    let min = sums.iter().min().unwrap_or(&0);
+
+    max - min
+}
+
+/// Indices of elves, heaviest first.
+fn heaviest_first(totals: &[u64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..totals.len()).collect();
+    order.sort_by_key(|&elf| std::cmp::Reverse(totals[elf]));
+
+    order
+}
+
+struct Search<'a> {
+    totals: &'a [u64],
+    order: Vec<usize>,
+    /// Sum of totals of elves from `order[i..]`
+    remaining: Vec<u64>,
+    /// Spread can't go below that, no matter the assignment
+    ideal_spread: u64,
+    sums: Vec<u64>,
+    assignment: Vec<usize>,
+    best_spread: u64,
+    best_assignment: Vec<usize>,
+}
+
+impl Search<'_> {
+    fn lower_bound(&self, depth: usize) -> u64 {
+        let teams_count = self.sums.len() as u64;
+        let total: u64 = self.sums.iter().sum::<u64>() + self.remaining[depth];
+
+        // Heaviest team can only get heavier, lightest one can only get as
+        // heavy as all remaining elves make it
+        let max = *self.sums.iter().max().unwrap();
+        let min = *self.sums.iter().min().unwrap() + self.remaining[depth];
+
+        max.max(total.div_ceil(teams_count))
+            .saturating_sub(min.min(total / teams_count))
+    }
+
+    fn run(&mut self, depth: usize) {
+        if self.best_spread == self.ideal_spread || self.lower_bound(depth) >= self.best_spread {
+            return;
+        }
+
+        if depth == self.order.len() {
+            self.best_spread = spread(&self.sums);
+            self.best_assignment = self.assignment.clone();
+            return;
+        }
+
+        let elf = self.order[depth];
+        for team in 0..self.sums.len() {
+            // Teams with equal sums are interchangeable (which also covers
+            // empty teams), so it's enough to try only the first of them
+            if self.sums[..team].contains(&self.sums[team]) {
+                continue;
+            }
+
+            self.sums[team] += self.totals[elf];
+            self.assignment[elf] = team;
+            self.run(depth + 1);
+            self.sums[team] -= self.totals[elf];
+        }
+    }
+}
+
+/// Finds partition of elves into `teams_count` teams with the smallest
+/// possible spread, with branch and bound search. Takes exponential time, so
+/// refuses inputs of more than [`EXACT_MAX_ELVES`] elves.
+pub fn partition_exact(totals: &[u64], teams_count: usize) -> Result<Partition, anyhow::Error> {
+    if teams_count == 0 {
+        bail!("number of teams must be positive");
+    }
+    if totals.len() > EXACT_MAX_ELVES {
+        bail!(
+            "exact partitioning is limited to {} elves, got {}",
+            EXACT_MAX_ELVES,
+            totals.len()
+        );
+    }
+
+    // Heuristic solution makes for a good initial bound
+    let initial = partition_heuristic(totals, teams_count)?;
+    let mut initial_assignment = vec![0; totals.len()];
+    for (team, elves) in initial.teams.iter().enumerate() {
+        for &elf in elves {
+            initial_assignment[elf] = team;
+        }
+    }
+
+    let order = heaviest_first(totals);
+    let mut remaining = vec![0; order.len() + 1];
+    for i in (0..order.len()).rev() {
+        remaining[i] = remaining[i + 1] + totals[order[i]];
+    }
+
+    let total: u64 = totals.iter().sum();
+    let ideal_spread = if totals.len() < teams_count {
+        // Some teams are going to stay empty
+        *totals.iter().min().unwrap_or(&0)
+    } else {
+        u64::from(!total.is_multiple_of(teams_count as u64))
+    };
+
+    let mut search = Search {
+        totals,
+        order,
+        remaining,
+        ideal_spread,
+        sums: vec![0; teams_count],
+        assignment: vec![0; totals.len()],
+        best_spread: initial.spread(),
+        best_assignment: initial_assignment,
+    };
+    search.run(0);
+
+    Ok(Partition::new(totals, &search.best_assignment, teams_count))
+}
+
+/// Finds the best move of a single elf from `heavy` to `light` team, or swap
+/// of a pair of elves between them, which brings their sums closer together.
+/// Returns elves to move out of `heavy` and out of `light` team.
+fn find_improvement(
+    totals: &[u64],
+    partition: &Partition,
+    heavy: usize,
+    light: usize,
+) -> Option<(usize, Option<usize>)> {
+    let gap = partition.sums[heavy] - partition.sums[light];
+    // Moving `d` calories from heavy to light team changes their difference
+    // to `|gap - 2d|`, which is the smallest for `d` closest to half the gap
+    let score = |d: u64| (gap as i64 - 2 * d as i64).unsigned_abs();
+
+    let mut best: Option<(u64, (usize, Option<usize>))> = None;
+    let mut consider = |d: u64, candidate: (usize, Option<usize>)| {
+        if d > 0 && d < gap && best.is_none_or(|(s, _)| score(d) < s) {
+            best = Some((score(d), candidate));
+        }
+    };
+
+    for &a in &partition.teams[heavy] {
+        consider(totals[a], (a, None));
+
+        for &b in &partition.teams[light] {
+            if totals[a] > totals[b] {
+                consider(totals[a] - totals[b], (a, Some(b)));
+            }
+        }
+    }
+
+    best.map(|(_, candidate)| candidate)
+}
+
+/// Finds partition of elves into `teams_count` teams quickly, by assigning
+/// every elf (heaviest first) to the currently lightest team, and then moving
+/// and swapping elves between pairs of teams for as long as it evens them out.
+pub fn partition_heuristic(totals: &[u64], teams_count: usize) -> Result<Partition, anyhow::Error> {
+    if teams_count == 0 {
+        bail!("number of teams must be positive");
+    }
+
+    let mut assignment = vec![0; totals.len()];
+    let mut sums = vec![0; teams_count];
+    for elf in heaviest_first(totals) {
+        let (team, _) = sums.iter().enumerate().min_by_key(|&(_, s)| s).unwrap();
+        assignment[elf] = team;
+        sums[team] += totals[elf];
+    }
+
+    let mut partition = Partition::new(totals, &assignment, teams_count);
+
+    // Every improvement strictly decreases sum of squares of team sums, so
+    // this terminates
+    'search: loop {
+        let mut teams: Vec<usize> = (0..teams_count).collect();
+        teams.sort_by_key(|&team| std::cmp::Reverse(partition.sums[team]));
+
+        for (i, &heavy) in teams.iter().enumerate() {
+            for &light in teams[i + 1..].iter().rev() {
+                let Some((a, b)) = find_improvement(totals, &partition, heavy, light) else {
+                    continue;
+                };
+
+                partition.teams[heavy].retain(|&elf| elf != a);
+                partition.teams[light].push(a);
+                partition.sums[heavy] -= totals[a];
+                partition.sums[light] += totals[a];
+
+                if let Some(b) = b {
+                    partition.teams[light].retain(|&elf| elf != b);
+                    partition.teams[heavy].push(b);
+                    partition.sums[light] -= totals[b];
+                    partition.sums[heavy] += totals[b];
+                }
+
+                continue 'search;
+            }
+        }
+
+        break;
+    }
+
+    for team in partition.teams.iter_mut() {
+        team.sort_unstable();
+    }
+
+    Ok(partition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOTALS: [u64; 5] = [6000, 4000, 11000, 24000, 10000];
+
+    #[test]
+    fn test_partition_exact() {
+        let partition = partition_exact(&TOTALS, 2).unwrap();
+        assert_eq!(partition.spread(), 1000);
+        assert_eq!(partition.sums.iter().sum::<u64>(), 55000);
+
+        let partition = partition_exact(&TOTALS, 3).unwrap();
+        assert_eq!(partition.spread(), 9000);
+        assert!(partition.teams.contains(&vec![3]));
+    }
+
+    #[test]
+    fn test_partition_exact_more_teams_than_elves() {
+        let partition = partition_exact(&[5, 3], 3).unwrap();
+
+        assert_eq!(partition.spread(), 5);
+        assert_eq!(partition.teams.iter().filter(|t| t.is_empty()).count(), 1);
+    }
+
+    #[test]
+    fn test_partition_exact_too_many_elves() {
+        assert!(partition_exact(&[1; EXACT_MAX_ELVES + 1], 2).is_err());
+        assert!(partition_exact(&TOTALS, 0).is_err());
+    }
+
+    #[test]
+    fn test_partition_heuristic() {
+        // Greedy assignment alone ends up with teams of 3 + 2 + 2 and 3 + 2
+        let totals = [3, 3, 2, 2, 2];
+        let partition = partition_heuristic(&totals, 2).unwrap();
+
+        assert_eq!(partition.spread(), 0);
+        assert_eq!(partition.teams.concat().len(), totals.len());
+
+        assert_eq!(partition_heuristic(&TOTALS, 3).unwrap().spread(), 9000);
+    }
+
+    fn brute_force_spread(totals: &[u64], teams_count: usize) -> u64 {
+        let assignments = teams_count.pow(totals.len() as u32);
+
+        (0..assignments)
+            .map(|mut assignment| {
+                let mut sums = vec![0; teams_count];
+                for total in totals {
+                    sums[assignment % teams_count] += total;
+                    assignment /= teams_count;
+                }
+
+                spread(&sums)
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_partition_exact_matches_brute_force() {
+        let totals = [47193, 31482, 65391, 12047, 38216, 59320, 22184, 41673];
+
+        for teams_count in 2..=4 {
+            let exact = partition_exact(&totals, teams_count).unwrap();
+            let heuristic = partition_heuristic(&totals, teams_count).unwrap();
+
+            assert_eq!(exact.spread(), brute_force_spread(&totals, teams_count));
+            assert!(exact.spread() <= heuristic.spread());
+            assert_eq!(exact.sums.iter().sum::<u64>(), totals.iter().sum::<u64>());
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Statistics over calories carried by elves.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use anyhow::bail;
+
+use crate::sum_inventories;
+
+/// Percentiles included in the report
+const PERCENTILES: [u8; 5] = [10, 25, 75, 90, 99];
+const HISTOGRAM_BUCKETS: usize = 10;
+const HISTOGRAM_WIDTH: usize = 40;
+/// How many median absolute deviations away from the median makes an outlier
+const OUTLIER_THRESHOLD: f64 = 3.0;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Elf {
+    pub index: usize,
+    pub items: usize,
+    pub total: u64,
+    /// 1 for the elf carrying the most calories, elves with equal totals share
+    /// the rank
+    pub rank: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bucket {
+    pub from: u64,
+    /// Exclusive, except for the last bucket
+    pub to: u64,
+    pub elves: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outlier {
+    pub index: usize,
+    pub total: u64,
+    /// Signed distance from the median, in median absolute deviations (or mean
+    /// absolute deviations, when most elves carry the median total)
+    pub deviations: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    pub elves: Vec<Elf>,
+    pub mean: f64,
+    pub median: f64,
+    /// Nearest-rank percentiles of totals
+    pub percentiles: Vec<(u8, u64)>,
+    pub histogram: Vec<Bucket>,
+    /// Number of elves by number of items they carry
+    pub item_counts: BTreeMap<usize, usize>,
+    pub outliers: Vec<Outlier>,
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+fn percentile(sorted: &[u64], p: u8) -> u64 {
+    let rank = (p as f64 / 100.0 * sorted.len() as f64).ceil() as usize;
+
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn histogram(sorted: &[u64]) -> Vec<Bucket> {
+    let min = sorted[0];
+    let max = sorted[sorted.len() - 1];
+    let width = ((max - min) / HISTOGRAM_BUCKETS as u64).max(1);
+
+    let mut buckets: Vec<Bucket> = (0..HISTOGRAM_BUCKETS as u64)
+        .map(|i| Bucket {
+            from: min + i * width,
+            to: min + (i + 1) * width,
+            elves: 0,
+        })
+        .take_while(|bucket| bucket.from <= max)
+        .collect();
+
+    let last = buckets.len() - 1;
+    buckets[last].to = max;
+
+    for &total in sorted {
+        let bucket = (((total - min) / width) as usize).min(last);
+        buckets[bucket].elves += 1;
+    }
+
+    buckets
+}
+
+impl Report {
+    pub fn new(inventories: &[Vec<u64>]) -> Result<Self, anyhow::Error> {
+        if inventories.is_empty() {
+            bail!("can't report on empty list of inventories");
+        }
+
+        let totals = sum_inventories(inventories);
+        let mut sorted = totals.clone();
+        sorted.sort_unstable();
+
+        let elves = inventories
+            .iter()
+            .zip(&totals)
+            .enumerate()
+            .map(|(index, (inventory, &total))| Elf {
+                index,
+                items: inventory.len(),
+                total,
+                rank: sorted.len() - sorted.partition_point(|&t| t <= total) + 1,
+            })
+            .collect();
+
+        let mean = totals.iter().sum::<u64>() as f64 / totals.len() as f64;
+        let sorted_f64: Vec<f64> = sorted.iter().map(|&t| t as f64).collect();
+        let median = median(&sorted_f64);
+
+        let percentiles = PERCENTILES
+            .iter()
+            .map(|&p| (p, percentile(&sorted, p)))
+            .collect();
+
+        let mut item_counts = BTreeMap::new();
+        for inventory in inventories {
+            *item_counts.entry(inventory.len()).or_insert(0) += 1;
+        }
+
+        let mut absolute_deviations: Vec<f64> =
+            sorted_f64.iter().map(|t| (t - median).abs()).collect();
+        absolute_deviations.sort_unstable_by(f64::total_cmp);
+        let mut scale = self::median(&absolute_deviations);
+        if scale == 0.0 {
+            scale = absolute_deviations.iter().sum::<f64>() / absolute_deviations.len() as f64;
+        }
+
+        let outliers = totals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &total)| {
+                let deviations = (total as f64 - median) / scale;
+                if scale == 0.0 || deviations.abs() <= OUTLIER_THRESHOLD {
+                    return None;
+                }
+
+                Some(Outlier {
+                    index,
+                    total,
+                    deviations,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            elves,
+            mean,
+            median,
+            percentiles,
+            histogram: histogram(&sorted),
+            item_counts,
+            outliers,
+        })
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Elves: {}", self.elves.len())?;
+        writeln!(f, "Mean: {:.1}", self.mean)?;
+        writeln!(f, "Median: {:.1}", self.median)?;
+        for (p, value) in &self.percentiles {
+            writeln!(f, "P{}: {}", p, value)?;
+        }
+
+        writeln!(f, "\nTotals histogram:")?;
+        let max_elves = self.histogram.iter().map(|b| b.elves).max().unwrap_or(0);
+        let range_width = self
+            .histogram
+            .iter()
+            .map(|b| format!("{}-{}", b.from, b.to).len())
+            .max()
+            .unwrap_or(0);
+        for bucket in &self.histogram {
+            let bar = bucket.elves * HISTOGRAM_WIDTH / max_elves.max(1);
+            writeln!(
+                f,
+                "{:>range_width$} | {} {}",
+                format!("{}-{}", bucket.from, bucket.to),
+                "#".repeat(bar),
+                bucket.elves
+            )?;
+        }
+
+        writeln!(f, "\nItems per elf:")?;
+        for (items, elves) in &self.item_counts {
+            writeln!(f, "{:>3} items: {} elves", items, elves)?;
+        }
+
+        writeln!(f, "\nOutliers:")?;
+        if self.outliers.is_empty() {
+            writeln!(f, "none")?;
+        }
+        for outlier in &self.outliers {
+            writeln!(
+                f,
+                "elf {}: {} ({:+.1} MAD)",
+                outlier.index, outlier.total, outlier.deviations
+            )?;
+        }
+
+        writeln!(f, "\nRank  Elf  Items  Total")?;
+        let mut elves: Vec<&Elf> = self.elves.iter().collect();
+        elves.sort_by_key(|elf| (elf.rank, elf.index));
+        for elf in elves {
+            writeln!(
+                f,
+                "{:>4}  {:>3}  {:>5}  {:>5}",
+                elf.rank, elf.index, elf.items, elf.total
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_inventories() -> Vec<Vec<u64>> {
+        vec![
+            vec![1000, 2000, 3000],
+            vec![4000],
+            vec![5000, 6000],
+            vec![7000, 8000, 9000],
+            vec![10000],
+        ]
+    }
+
+    #[test]
+    fn test_report() {
+        let report = Report::new(&test_inventories()).unwrap();
+
+        assert_eq!(
+            report.elves.iter().map(|e| e.rank).collect::<Vec<_>>(),
+            vec![4, 5, 2, 1, 3]
+        );
+        assert_eq!(report.mean, 11000.0);
+        assert_eq!(report.median, 10000.0);
+        assert_eq!(
+            report.percentiles,
+            vec![
+                (10, 4000),
+                (25, 6000),
+                (75, 11000),
+                (90, 24000),
+                (99, 24000)
+            ]
+        );
+        assert_eq!(report.item_counts, BTreeMap::from([(1, 2), (2, 1), (3, 2)]));
+        assert_eq!(
+            report.outliers,
+            vec![Outlier {
+                index: 3,
+                total: 24000,
+                deviations: 14000.0 / 4000.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        let buckets = histogram(&[4000, 6000, 10000, 11000, 24000]);
+
+        assert_eq!(buckets.len(), 10);
+        assert_eq!(buckets[0].from, 4000);
+        assert_eq!(buckets[9].to, 24000);
+        assert_eq!(
+            buckets.iter().map(|b| b.elves).collect::<Vec<_>>(),
+            vec![1, 1, 0, 2, 0, 0, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_shared_ranks() {
+        let report = Report::new(&[vec![5], vec![7], vec![5], vec![1]]).unwrap();
+
+        assert_eq!(
+            report.elves.iter().map(|e| e.rank).collect::<Vec<_>>(),
+            vec![2, 1, 2, 4]
+        );
+    }
+
+    #[test]
+    fn test_outliers_with_zero_mad() {
+        let report = Report::new(&[vec![5], vec![5], vec![5], vec![5], vec![50]]).unwrap();
+
+        assert_eq!(
+            report.outliers,
+            vec![Outlier {
+                index: 4,
+                total: 50,
+                deviations: 5.0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_empty_inventories() {
+        assert!(Report::new(&[]).is_err());
+    }
+}
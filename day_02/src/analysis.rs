@@ -0,0 +1,172 @@
+//! Scoring strategy guide under every possible way of decoding X/Y/Z symbols.
+
+use std::fmt::Display;
+
+use crate::{match_shape_to_desired_game_result, score_game, GameResult, Shape, Symbol};
+
+const PERMUTATIONS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// Meaning of X, Y and Z symbols, respectively.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decoding {
+    Shapes([Shape; 3]),
+    Results([GameResult; 3]),
+}
+
+impl Decoding {
+    /// Returns all 6 permutations of shapes and 6 permutations of results.
+    pub fn all() -> Vec<Self> {
+        let shapes = [Shape::Rock, Shape::Paper, Shape::Scissors];
+        let results = [GameResult::Lose, GameResult::Draw, GameResult::Win];
+
+        let shape_decodings = PERMUTATIONS
+            .iter()
+            .map(|p| Self::Shapes(p.map(|i| shapes[i].clone())));
+        let result_decodings = PERMUTATIONS
+            .iter()
+            .map(|p| Self::Results(p.map(|i| results[i])));
+
+        shape_decodings.chain(result_decodings).collect()
+    }
+
+    fn score(&self, opponent_symbol: &Symbol, player_symbol: &Symbol) -> u64 {
+        let opponent_shape: Shape = opponent_symbol.clone().into();
+        let idx = match player_symbol {
+            Symbol::AX => 0,
+            Symbol::BY => 1,
+            Symbol::CZ => 2,
+        };
+
+        match self {
+            Self::Shapes(shapes) => score_game(&opponent_shape, &shapes[idx]),
+            Self::Results(results) => score_game(
+                &opponent_shape,
+                &match_shape_to_desired_game_result(&opponent_shape, &results[idx]),
+            ),
+        }
+    }
+}
+
+impl Display for Decoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let meanings: Vec<String> = match self {
+            Self::Shapes(shapes) => shapes.iter().map(|s| format!("{:?}", s)).collect(),
+            Self::Results(results) => results.iter().map(|r| format!("{:?}", r)).collect(),
+        };
+
+        write!(f, "X={} Y={} Z={}", meanings[0], meanings[1], meanings[2])
+    }
+}
+
+/// Total score of the strategy guide under every [`Decoding`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    pub scores: Vec<(Decoding, u64)>,
+}
+
+impl Analysis {
+    pub fn new(games: &[(Symbol, Symbol)]) -> Self {
+        let scores = Decoding::all()
+            .into_iter()
+            .map(|decoding| {
+                let score = games
+                    .iter()
+                    .map(|(opponent, player)| decoding.score(opponent, player))
+                    .sum();
+
+                (decoding, score)
+            })
+            .collect();
+
+        Self { scores }
+    }
+
+    pub fn best(&self) -> &(Decoding, u64) {
+        self.scores.iter().max_by_key(|(_, score)| score).unwrap()
+    }
+
+    pub fn worst(&self) -> &(Decoding, u64) {
+        self.scores.iter().min_by_key(|(_, score)| score).unwrap()
+    }
+
+    /// Finds decoding which scores the closest to `target`.
+    pub fn closest_to(&self, target: u64) -> &(Decoding, u64) {
+        self.scores
+            .iter()
+            .min_by_key(|(_, score)| score.abs_diff(target))
+            .unwrap()
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut scores: Vec<_> = self.scores.iter().collect();
+        scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+        for (decoding, score) in scores {
+            writeln!(f, "{:<30} {}", decoding.to_string(), score)?;
+        }
+
+        let (best, best_score) = self.best();
+        let (worst, worst_score) = self.worst();
+        writeln!(f, "\nBest: {} ({})", best, best_score)?;
+        writeln!(f, "Worst: {} ({})", worst, worst_score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GameResult::*;
+    use Shape::*;
+    use Symbol::*;
+
+    fn test_games() -> Vec<(Symbol, Symbol)> {
+        vec![(AX, BY), (BY, AX), (CZ, CZ)]
+    }
+
+    #[test]
+    fn test_analysis_includes_puzzle_decodings() {
+        let analysis = Analysis::new(&test_games());
+
+        assert_eq!(analysis.scores.len(), 12);
+        assert!(analysis
+            .scores
+            .contains(&(Decoding::Shapes([Rock, Paper, Scissors]), 15)));
+        assert!(analysis
+            .scores
+            .contains(&(Decoding::Results([Lose, Draw, Win]), 12)));
+    }
+
+    #[test]
+    fn test_best_and_worst() {
+        let analysis = Analysis::new(&test_games());
+
+        assert_eq!(
+            analysis.best(),
+            &(Decoding::Shapes([Scissors, Paper, Rock]), 24)
+        );
+        assert_eq!(
+            analysis.worst(),
+            &(Decoding::Shapes([Rock, Scissors, Paper]), 6)
+        );
+    }
+
+    #[test]
+    fn test_closest_to() {
+        let analysis = Analysis::new(&test_games());
+
+        assert_eq!(
+            analysis.closest_to(18),
+            &(Decoding::Results([Win, Lose, Draw]), 18)
+        );
+        assert_eq!(analysis.closest_to(18).0.to_string(), "X=Win Y=Lose Z=Draw");
+    }
+}
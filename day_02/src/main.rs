@@ -1,11 +1,25 @@
 use std::str::FromStr;
 
 use anyhow::{anyhow, bail, Context};
-use common::{get_arg, read_file_to_string};
+#[cfg(not(feature = "serde"))]
+use common::read_problem;
+#[cfg(feature = "serde")]
+use common::serialization::read_problem;
+use common::{get_arg, get_option, has_flag, profiling::profile, read_file_to_string};
+use rand::{rngs::StdRng, SeedableRng};
+
+mod analysis;
+mod rules;
+mod tournament;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 /// Abstract representation of input, to be later type-safely interpreted as
 /// either `Shape` or `GameResult`.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Symbol {
     AX,
     BY,
@@ -44,7 +58,7 @@ impl From<Symbol> for Shape {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum GameResult {
     Lose,
     Draw,
@@ -70,6 +84,7 @@ fn parse_game(s: &str) -> Result<(Symbol, Symbol), anyhow::Error> {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Problem {
     games: Vec<(Symbol, Symbol)>,
 }
@@ -131,29 +146,97 @@ fn match_shape_to_desired_game_result(
 
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
-    let input_string = read_file_to_string(&input_file_path)?;
-    let Problem { games } = input_string.parse()?;
+
+    if let Some(rules_path) = get_option("--rules") {
+        let rules: rules::Rules = common::read_problem(&rules_path)
+            .with_context(|| format!("reading rules from {}", rules_path))?;
+        let games = rules::parse_games(&read_file_to_string(&input_file_path)?)?;
+
+        println!(
+            "Part 1 solution: {}",
+            profile("part 1", || rules.score_part_1(&games))?
+        );
+        println!(
+            "Part 2 solution: {}",
+            profile("part 2", || rules.score_part_2(&games))?
+        );
+
+        return Ok(());
+    }
+
+    let Problem { games } = read_problem(&input_file_path)?;
+
+    if has_flag("--tournament") {
+        let bots = get_option("--bots")
+            .unwrap_or_else(|| "random,frequency,markov,cycle:RPS".to_string())
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<tournament::Bot>, _>>()?;
+        let rounds = match get_option("--rounds") {
+            Some(rounds) => rounds.parse().context("couldn't parse --rounds")?,
+            None => games.len(),
+        };
+        let runs = match get_option("--runs") {
+            Some(runs) => runs.parse().context("couldn't parse --runs")?,
+            None => 100,
+        };
+        let mut rng = match get_option("--seed") {
+            Some(seed) => StdRng::seed_from_u64(seed.parse().context("couldn't parse --seed")?),
+            None => StdRng::from_entropy(),
+        };
+
+        let moves: Vec<Shape> = games.into_iter().map(|(_, player)| player.into()).collect();
+        let stats = profile("tournament", || {
+            tournament::play_tournament(&moves, &bots, rounds, runs, &mut rng)
+        })?;
+        print!("{}", tournament::render_stats(&stats));
+
+        return Ok(());
+    }
+
+    if has_flag("--analyze") {
+        let analysis = profile("analysis", || analysis::Analysis::new(&games));
+        print!("{}", analysis);
+
+        if let Some(target) = get_option("--target") {
+            let target = target
+                .parse()
+                .with_context(|| format!("couldn't parse --target from '{}'", target))?;
+            let (decoding, score) = analysis.closest_to(target);
+
+            println!("Closest to {}: {} ({})", target, decoding, score);
+        }
+
+        return Ok(());
+    }
 
     // score games using straightforward interpretation of the input
-    let games_score_pt1: u64 = games
-        .clone()
-        .into_iter()
-        .map(|(opponent_symbol, player_symbol)| {
-            score_game(&opponent_symbol.into(), &player_symbol.into())
-        })
-        .sum();
+    let games_score_pt1: u64 = profile("part 1", || {
+        games
+            .clone()
+            .into_iter()
+            .map(|(opponent_symbol, player_symbol)| {
+                score_game(&opponent_symbol.into(), &player_symbol.into())
+            })
+            .sum()
+    });
 
     // score games using alternate interpretation of the input, where second
     // symbol of each pair is a desired game result
-    let games_score_pt2: u64 = games
-        .into_iter()
-        .map(|(opponent_symbol, player_symbol)| {
-            score_game(
-                &opponent_symbol.clone().into(),
-                &match_shape_to_desired_game_result(&opponent_symbol.into(), &player_symbol.into()),
-            )
-        })
-        .sum();
+    let games_score_pt2: u64 = profile("part 2", || {
+        games
+            .into_iter()
+            .map(|(opponent_symbol, player_symbol)| {
+                score_game(
+                    &opponent_symbol.clone().into(),
+                    &match_shape_to_desired_game_result(
+                        &opponent_symbol.into(),
+                        &player_symbol.into(),
+                    ),
+                )
+            })
+            .sum()
+    });
 
     println!("Part 1 solution: {}", games_score_pt1);
     println!("Part 2 solution: {}", games_score_pt2);
@@ -0,0 +1,476 @@
+//! Hand games defined by a table of rules, generalising Rock-Paper-Scissors to
+//! any number of shapes.
+//!
+//! Rules are read from a text file of `key: item, item, ...` lines, e.g.
+//!
+//! ```text
+//! # comment
+//! shapes: Rock 1, Paper 2, Scissors 3
+//! beats: Rock > Scissors, Paper > Rock, Scissors > Paper
+//! outcomes: lose 0, draw 3, win 6
+//! opponent: A Rock, B Paper, C Scissors
+//! player: X Rock, Y Paper, Z Scissors
+//! results: X lose, Y draw, Z win
+//! ```
+//!
+//! Keys can be repeated, with items of all lines combined.
+
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::GameResult;
+
+const KEYS: [&str; 6] = [
+    "shapes", "beats", "outcomes", "opponent", "player", "results",
+];
+
+/// Index of a shape in [`Rules::shapes`]
+pub type ShapeId = usize;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rules {
+    pub shapes: Vec<String>,
+    pub shape_scores: Vec<u64>,
+    /// `beats[a][b]` is `true` when shape `a` beats shape `b`
+    pub beats: Vec<Vec<bool>>,
+    /// Scores for losing, drawing and winning a game
+    pub outcome_scores: [u64; 3],
+    pub opponent_symbols: HashMap<String, ShapeId>,
+    pub player_symbols: HashMap<String, ShapeId>,
+    pub result_symbols: HashMap<String, GameResult>,
+}
+
+fn outcome_index(result: &GameResult) -> usize {
+    match result {
+        GameResult::Lose => 0,
+        GameResult::Draw => 1,
+        GameResult::Win => 2,
+    }
+}
+
+fn parse_result(s: &str) -> Result<GameResult, anyhow::Error> {
+    match s {
+        "lose" => Ok(GameResult::Lose),
+        "draw" => Ok(GameResult::Draw),
+        "win" => Ok(GameResult::Win),
+        other => bail!("unknown game result: {}", other),
+    }
+}
+
+/// Splits `item` into exactly two whitespace separated words.
+fn split_pair(item: &str) -> Result<(&str, &str), anyhow::Error> {
+    let mut words = item.split_whitespace();
+
+    match (words.next(), words.next(), words.next()) {
+        (Some(a), Some(b), None) => Ok((a, b)),
+        _ => bail!("expected two words, got '{}'", item),
+    }
+}
+
+impl FromStr for Rules {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        for (line_number, line) in (1..).zip(s.lines()) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, items) = line.split_once(':').ok_or_else(|| {
+                anyhow!(
+                    "line {}: expected 'key: items', got '{}'",
+                    line_number,
+                    line
+                )
+            })?;
+
+            let key = key.trim();
+            if !KEYS.contains(&key) {
+                bail!("line {}: unknown key '{}'", line_number, key);
+            }
+
+            entries
+                .entry(key)
+                .or_default()
+                .extend(items.split(',').map(str::trim));
+        }
+
+        let items = |key: &str| entries.get(key).cloned().unwrap_or_default();
+
+        let mut shapes = vec![];
+        let mut shape_scores = vec![];
+        for item in items("shapes") {
+            let (name, score) = split_pair(item).context("parsing shapes")?;
+            if shapes.iter().any(|shape| shape == name) {
+                bail!("duplicate shape: {}", name);
+            }
+
+            shapes.push(name.to_string());
+            shape_scores.push(
+                score
+                    .parse()
+                    .with_context(|| format!("couldn't parse score of {}", name))?,
+            );
+        }
+
+        let shape_id = |name: &str| {
+            shapes
+                .iter()
+                .position(|shape| shape == name)
+                .ok_or_else(|| anyhow!("unknown shape: {}", name))
+        };
+
+        let mut beats = vec![vec![false; shapes.len()]; shapes.len()];
+        for item in items("beats") {
+            let (winner, loser) = item
+                .split_once('>')
+                .ok_or_else(|| anyhow!("expected 'winner > loser', got '{}'", item))?;
+
+            beats[shape_id(winner.trim())?][shape_id(loser.trim())?] = true;
+        }
+
+        let mut outcome_scores = [None; 3];
+        for item in items("outcomes") {
+            let (result, score) = split_pair(item).context("parsing outcomes")?;
+            let score = score
+                .parse()
+                .with_context(|| format!("couldn't parse score of {}", result))?;
+
+            let outcome = &mut outcome_scores[outcome_index(&parse_result(result)?)];
+            if outcome.is_some() {
+                bail!("duplicate outcome: {}", result);
+            }
+
+            *outcome = Some(score);
+        }
+        let [Some(lose), Some(draw), Some(win)] = outcome_scores else {
+            bail!("outcomes must list scores for lose, draw and win");
+        };
+
+        let symbols = |key: &str| -> Result<HashMap<String, ShapeId>, anyhow::Error> {
+            let mut symbols = HashMap::new();
+            for item in items(key) {
+                let (symbol, shape) =
+                    split_pair(item).with_context(|| format!("parsing {}", key))?;
+                if symbols
+                    .insert(symbol.to_string(), shape_id(shape)?)
+                    .is_some()
+                {
+                    bail!("duplicate {} symbol: {}", key, symbol);
+                }
+            }
+
+            Ok(symbols)
+        };
+
+        let mut result_symbols = HashMap::new();
+        for item in items("results") {
+            let (symbol, result) = split_pair(item).context("parsing results")?;
+            if result_symbols
+                .insert(symbol.to_string(), parse_result(result)?)
+                .is_some()
+            {
+                bail!("duplicate result symbol: {}", symbol);
+            }
+        }
+
+        let rules = Self {
+            opponent_symbols: symbols("opponent")?,
+            player_symbols: symbols("player")?,
+            shapes,
+            shape_scores,
+            beats,
+            outcome_scores: [lose, draw, win],
+            result_symbols,
+        };
+        rules.validate()?;
+
+        Ok(rules)
+    }
+}
+
+impl Rules {
+    /// Checks that every pair of different shapes has exactly one winner, and
+    /// that every shape can be both beaten and beaten with, so that any desired
+    /// game result can be achieved.
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.shapes.len() < 3 {
+            bail!("game needs at least 3 shapes, got {}", self.shapes.len());
+        }
+
+        for a in 0..self.shapes.len() {
+            if self.beats[a][a] {
+                bail!("{} can't beat itself", self.shapes[a]);
+            }
+
+            for b in (a + 1)..self.shapes.len() {
+                match (self.beats[a][b], self.beats[b][a]) {
+                    (true, true) => bail!(
+                        "{} and {} can't beat each other",
+                        self.shapes[a],
+                        self.shapes[b]
+                    ),
+                    (false, false) => bail!(
+                        "missing winner between {} and {}",
+                        self.shapes[a],
+                        self.shapes[b]
+                    ),
+                    _ => {}
+                }
+            }
+
+            if !self.beats[a].contains(&true) {
+                bail!("{} doesn't beat any shape", self.shapes[a]);
+            }
+            if !self.beats.iter().any(|row| row[a]) {
+                bail!("{} isn't beaten by any shape", self.shapes[a]);
+            }
+        }
+
+        if self.opponent_symbols.is_empty() || self.player_symbols.is_empty() {
+            bail!("both opponent and player symbols are required");
+        }
+
+        Ok(())
+    }
+
+    pub fn game_result(&self, opponent_shape: ShapeId, player_shape: ShapeId) -> GameResult {
+        if self.beats[player_shape][opponent_shape] {
+            GameResult::Win
+        } else if self.beats[opponent_shape][player_shape] {
+            GameResult::Lose
+        } else {
+            GameResult::Draw
+        }
+    }
+
+    /// Scores a game, awarding points for both its outcome and shape selected
+    /// by the player.
+    pub fn score_game(&self, opponent_shape: ShapeId, player_shape: ShapeId) -> u64 {
+        let result = self.game_result(opponent_shape, player_shape);
+
+        self.shape_scores[player_shape] + self.outcome_scores[outcome_index(&result)]
+    }
+
+    /// Given opponent's shape and desired game result returns shape that
+    /// satisfies that outcome. When there are several such shapes, the one
+    /// scoring the most points is chosen.
+    pub fn match_shape_to_desired_game_result(
+        &self,
+        opponent_shape: ShapeId,
+        desired_game_result: &GameResult,
+    ) -> ShapeId {
+        (0..self.shapes.len())
+            .filter(|&shape| self.game_result(opponent_shape, shape) == *desired_game_result)
+            .max_by_key(|&shape| (self.shape_scores[shape], std::cmp::Reverse(shape)))
+            // Validation guarantees every result is achievable
+            .unwrap()
+    }
+
+    fn lookup<'a, T>(
+        symbols: &'a HashMap<String, T>,
+        symbol: &str,
+        kind: &str,
+    ) -> Result<&'a T, anyhow::Error> {
+        symbols
+            .get(symbol)
+            .ok_or_else(|| anyhow!("unknown {} symbol: {}", kind, symbol))
+    }
+
+    /// Scores games interpreting second symbol as player's shape.
+    pub fn score_part_1(&self, games: &[(String, String)]) -> Result<u64, anyhow::Error> {
+        games
+            .iter()
+            .map(|(opponent_symbol, player_symbol)| {
+                let opponent = Self::lookup(&self.opponent_symbols, opponent_symbol, "opponent")?;
+                let player = Self::lookup(&self.player_symbols, player_symbol, "player")?;
+
+                Ok(self.score_game(*opponent, *player))
+            })
+            .sum()
+    }
+
+    /// Scores games interpreting second symbol as desired game result.
+    pub fn score_part_2(&self, games: &[(String, String)]) -> Result<u64, anyhow::Error> {
+        games
+            .iter()
+            .map(|(opponent_symbol, result_symbol)| {
+                let opponent = Self::lookup(&self.opponent_symbols, opponent_symbol, "opponent")?;
+                let result = Self::lookup(&self.result_symbols, result_symbol, "result")?;
+                let player = self.match_shape_to_desired_game_result(*opponent, result);
+
+                Ok(self.score_game(*opponent, player))
+            })
+            .sum()
+    }
+}
+
+/// Parses strategy guide as pairs of raw symbols, to be interpreted by
+/// [`Rules`].
+pub fn parse_games(s: &str) -> Result<Vec<(String, String)>, anyhow::Error> {
+    (1..)
+        .zip(s.lines())
+        .map(|(line_number, line)| {
+            let (left, right) =
+                split_pair(line).with_context(|| format!("line {}", line_number))?;
+
+            Ok((left.to_string(), right.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Shape::{self, *};
+
+    const RPS: &str = include_str!("../rules/rps.txt");
+    const RPSLS: &str = include_str!("../rules/rpsls.txt");
+
+    #[test]
+    fn test_classic_rules() {
+        let rules: Rules = RPS.parse().unwrap();
+        let games = parse_games("A Y\nB X\nC Z").unwrap();
+
+        assert_eq!(rules.score_part_1(&games).unwrap(), 15);
+        assert_eq!(rules.score_part_2(&games).unwrap(), 12);
+    }
+
+    #[test]
+    fn test_classic_rules_match_hardcoded_game() {
+        let rules: Rules = RPS.parse().unwrap();
+        let shapes: [Shape; 3] = [Rock, Paper, Scissors];
+
+        for (opponent_id, opponent) in shapes.iter().enumerate() {
+            for (player_id, player) in shapes.iter().enumerate() {
+                assert_eq!(
+                    rules.score_game(opponent_id, player_id),
+                    crate::score_game(opponent, player)
+                );
+            }
+
+            for result in [GameResult::Lose, GameResult::Draw, GameResult::Win] {
+                let expected = crate::match_shape_to_desired_game_result(opponent, &result);
+
+                assert_eq!(
+                    rules.match_shape_to_desired_game_result(opponent_id, &result),
+                    expected as usize - 1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rpsls() {
+        let rules: Rules = RPSLS.parse().unwrap();
+        let games = parse_games("A Z\nD Y\nE X").unwrap();
+
+        // Spock vaporizes Rock, Lizard ties with Lizard, Spock smashes Scissors
+        assert_eq!(rules.score_part_1(&games).unwrap(), 11 + 7 + 3);
+        // Rock is beaten by both Paper and Spock, of which Spock scores more
+        assert_eq!(
+            rules.match_shape_to_desired_game_result(0, &GameResult::Win),
+            4
+        );
+        // Winning with Spock, drawing with Lizard, losing with Scissors
+        assert_eq!(rules.score_part_2(&games).unwrap(), 11 + 7 + 3);
+    }
+
+    #[test]
+    fn test_validation() {
+        let rules = |beats: &str| {
+            format!(
+                "shapes: R 1, P 2, S 3\nbeats: {}\noutcomes: lose 0, draw 3, win 6\n\
+                 opponent: A R\nplayer: X R",
+                beats
+            )
+            .parse::<Rules>()
+        };
+
+        assert!(rules("R > S, P > R, S > P").is_ok());
+        assert_eq!(
+            rules("R > S, P > R").unwrap_err().to_string(),
+            "missing winner between P and S"
+        );
+        assert_eq!(
+            rules("R > S, P > R, S > P, R > P").unwrap_err().to_string(),
+            "R and P can't beat each other"
+        );
+        assert_eq!(
+            rules("R > S, R > P, S > P").unwrap_err().to_string(),
+            "R isn't beaten by any shape"
+        );
+        assert_eq!(
+            rules("R > S, P > R, S > P, R > X").unwrap_err().to_string(),
+            "unknown shape: X"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_symbols() {
+        let rules = |symbols: &str| {
+            format!(
+                "shapes: R 1, P 2, S 3\nbeats: R > S, P > R, S > P\n\
+                 outcomes: lose 0, draw 3, win 6\n{}",
+                symbols
+            )
+            .parse::<Rules>()
+        };
+
+        assert!(rules("opponent: A R, B P\nplayer: X R\nresults: X lose, Y win").is_ok());
+        assert_eq!(
+            rules("opponent: A R, A P\nplayer: X R")
+                .unwrap_err()
+                .to_string(),
+            "duplicate opponent symbol: A"
+        );
+        // Repeated keys are combined, so duplicates are caught across lines
+        assert_eq!(
+            rules("opponent: A R\nplayer: X R\nplayer: X S")
+                .unwrap_err()
+                .to_string(),
+            "duplicate player symbol: X"
+        );
+        assert_eq!(
+            rules("opponent: A R\nplayer: X R\nresults: X lose, X win")
+                .unwrap_err()
+                .to_string(),
+            "duplicate result symbol: X"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_outcomes() {
+        let rules = |outcomes: &str| {
+            format!(
+                "shapes: R 1, P 2, S 3\nbeats: R > S, P > R, S > P\noutcomes: {}\n\
+                 opponent: A R\nplayer: X R",
+                outcomes
+            )
+            .parse::<Rules>()
+        };
+
+        assert!(rules("lose 0, draw 3, win 6").is_ok());
+        assert_eq!(
+            rules("lose 0, draw 3, win 6, draw 4")
+                .unwrap_err()
+                .to_string(),
+            "duplicate outcome: draw"
+        );
+    }
+
+    #[test]
+    fn test_unknown_symbol() {
+        let rules: Rules = RPS.parse().unwrap();
+        let games = parse_games("A W").unwrap();
+
+        assert_eq!(
+            rules.score_part_1(&games).unwrap_err().to_string(),
+            "unknown player symbol: W"
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Playing strategy guide's moves against adaptive opponents, to see how
+//! exploitable its move sequence is.
+
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+use crate::{match_shape_to_desired_game_result, score_game, GameResult, Shape};
+
+const SHAPES: [Shape; 3] = [Shape::Rock, Shape::Paper, Shape::Scissors];
+
+fn shape_index(shape: &Shape) -> usize {
+    shape.clone() as usize - 1
+}
+
+fn counter(shape: &Shape) -> Shape {
+    match_shape_to_desired_game_result(shape, &GameResult::Win)
+}
+
+fn game_result(opponent_shape: &Shape, player_shape: &Shape) -> GameResult {
+    [GameResult::Lose, GameResult::Draw, GameResult::Win]
+        .into_iter()
+        .find(|result| match_shape_to_desired_game_result(opponent_shape, result) == *player_shape)
+        .unwrap()
+}
+
+/// Opponent strategy, choosing shapes based on player's past moves.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Bot {
+    /// Picks shapes uniformly at random
+    Random,
+    /// Counters player's most frequent shape so far
+    Frequency,
+    /// Counters shape most often played by the player after their last one
+    Markov,
+    /// Plays given shapes in a loop
+    Cycle(Vec<Shape>),
+}
+
+impl FromStr for Bot {
+    type Err = anyhow::Error;
+
+    /// Parses `random`, `frequency`, `markov` or `cycle:SHAPES` where shapes
+    /// are given by their initials, e.g. `cycle:RRPS`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "frequency" => Ok(Self::Frequency),
+            "markov" => Ok(Self::Markov),
+            _ => {
+                let shapes = s
+                    .strip_prefix("cycle:")
+                    .ok_or_else(|| anyhow!("unknown bot: {}", s))?;
+                if shapes.is_empty() {
+                    bail!("cycle bot needs at least one shape");
+                }
+
+                let shapes = shapes
+                    .chars()
+                    .map(|c| match c {
+                        'R' => Ok(Shape::Rock),
+                        'P' => Ok(Shape::Paper),
+                        'S' => Ok(Shape::Scissors),
+                        other => bail!("unknown shape initial: {}", other),
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Self::Cycle(shapes))
+            }
+        }
+    }
+}
+
+impl Display for Bot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Random => write!(f, "random"),
+            Self::Frequency => write!(f, "frequency"),
+            Self::Markov => write!(f, "markov"),
+            Self::Cycle(shapes) => {
+                let initials: String = shapes
+                    .iter()
+                    .map(|shape| format!("{:?}", shape).remove(0))
+                    .collect();
+
+                write!(f, "cycle:{}", initials)
+            }
+        }
+    }
+}
+
+/// What a [`Bot`] knows about the player in the middle of a match.
+#[derive(Default)]
+struct History {
+    round: usize,
+    last: Option<usize>,
+    frequencies: [u64; 3],
+    transitions: [[u64; 3]; 3],
+}
+
+impl History {
+    fn observe(&mut self, player_shape: &Shape) {
+        let idx = shape_index(player_shape);
+
+        self.frequencies[idx] += 1;
+        if let Some(last) = self.last {
+            self.transitions[last][idx] += 1;
+        }
+        self.last = Some(idx);
+        self.round += 1;
+    }
+}
+
+/// Picks one of the most frequent shapes according to `counts`, breaking ties
+/// at random.
+fn most_frequent(counts: &[u64; 3], rng: &mut StdRng) -> Shape {
+    let max = counts.iter().max().unwrap();
+    let candidates: Vec<&Shape> = SHAPES
+        .iter()
+        .zip(counts)
+        .filter(|(_, count)| *count == max)
+        .map(|(shape, _)| shape)
+        .collect();
+
+    (*candidates.choose(rng).unwrap()).clone()
+}
+
+impl Bot {
+    fn choose(&self, history: &History, rng: &mut StdRng) -> Shape {
+        match self {
+            Self::Random => SHAPES[rng.gen_range(0..3)].clone(),
+            Self::Frequency => counter(&most_frequent(&history.frequencies, rng)),
+            Self::Markov => match history.last {
+                Some(last) => counter(&most_frequent(&history.transitions[last], rng)),
+                None => SHAPES[rng.gen_range(0..3)].clone(),
+            },
+            Self::Cycle(shapes) => shapes[history.round % shapes.len()].clone(),
+        }
+    }
+}
+
+/// Outcomes of all matches played against a single bot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BotStats {
+    pub bot: Bot,
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+    /// Player's total score of every match, in ascending order
+    pub scores: Vec<u64>,
+}
+
+impl BotStats {
+    fn games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+
+    fn rate(&self, count: u64) -> f64 {
+        count as f64 / self.games().max(1) as f64
+    }
+
+    pub fn mean_score(&self) -> f64 {
+        self.scores.iter().sum::<u64>() as f64 / self.scores.len().max(1) as f64
+    }
+
+    pub fn score_std_dev(&self) -> f64 {
+        let mean = self.mean_score();
+        let variance = self
+            .scores
+            .iter()
+            .map(|&score| (score as f64 - mean).powi(2))
+            .sum::<f64>()
+            / self.scores.len().max(1) as f64;
+
+        variance.sqrt()
+    }
+
+    /// Nearest-rank percentile of match scores.
+    pub fn score_percentile(&self, p: u8) -> u64 {
+        let rank = (p as f64 / 100.0 * self.scores.len() as f64).ceil() as usize;
+
+        self.scores[rank.clamp(1, self.scores.len()) - 1]
+    }
+}
+
+/// Plays `runs` matches of `rounds` rounds against every bot, with the player
+/// repeating `moves` of the strategy guide in order.
+pub fn play_tournament(
+    moves: &[Shape],
+    bots: &[Bot],
+    rounds: usize,
+    runs: usize,
+    rng: &mut StdRng,
+) -> Result<Vec<BotStats>, anyhow::Error> {
+    if moves.is_empty() {
+        bail!("strategy guide has no moves");
+    }
+    if runs == 0 {
+        bail!("tournament needs at least one run");
+    }
+
+    let stats = bots
+        .iter()
+        .map(|bot| {
+            let mut stats = BotStats {
+                bot: bot.clone(),
+                wins: 0,
+                draws: 0,
+                losses: 0,
+                scores: Vec::with_capacity(runs),
+            };
+
+            for _ in 0..runs {
+                let mut history = History::default();
+                let mut score = 0;
+
+                for player_shape in moves.iter().cycle().take(rounds) {
+                    let opponent_shape = bot.choose(&history, rng);
+
+                    score += score_game(&opponent_shape, player_shape);
+                    match game_result(&opponent_shape, player_shape) {
+                        GameResult::Win => stats.wins += 1,
+                        GameResult::Draw => stats.draws += 1,
+                        GameResult::Lose => stats.losses += 1,
+                    }
+
+                    history.observe(player_shape);
+                }
+
+                stats.scores.push(score);
+            }
+
+            stats.scores.sort_unstable();
+            stats
+        })
+        .collect();
+
+    Ok(stats)
+}
+
+/// Renders table of player's win, draw and loss rates and match score
+/// distribution against every bot.
+pub fn render_stats(stats: &[BotStats]) -> String {
+    let mut table = format!(
+        "{:<12} {:>6} {:>6} {:>6} {:>10} {:>8} {:>8} {:>8} {:>8}\n",
+        "bot", "win", "draw", "loss", "mean", "std dev", "min", "median", "max"
+    );
+
+    for s in stats {
+        table.push_str(&format!(
+            "{:<12} {:>5.1}% {:>5.1}% {:>5.1}% {:>10.1} {:>8.1} {:>8} {:>8} {:>8}\n",
+            s.bot.to_string(),
+            s.rate(s.wins) * 100.0,
+            s.rate(s.draws) * 100.0,
+            s.rate(s.losses) * 100.0,
+            s.mean_score(),
+            s.score_std_dev(),
+            s.score_percentile(0),
+            s.score_percentile(50),
+            s.score_percentile(100),
+        ));
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use Shape::*;
+
+    fn play(moves: &[Shape], bot: &str, rounds: usize, runs: usize) -> BotStats {
+        let mut rng = StdRng::seed_from_u64(2022);
+
+        play_tournament(moves, &[bot.parse().unwrap()], rounds, runs, &mut rng)
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn test_parse_bot() {
+        assert_eq!("markov".parse::<Bot>().unwrap(), Bot::Markov);
+        assert_eq!(
+            "cycle:RPS".parse::<Bot>().unwrap(),
+            Bot::Cycle(vec![Rock, Paper, Scissors])
+        );
+        assert_eq!(Bot::Cycle(vec![Rock, Rock, Paper]).to_string(), "cycle:RRP");
+        assert!("cycle:RX".parse::<Bot>().is_err());
+        assert!("psychic".parse::<Bot>().is_err());
+    }
+
+    #[test]
+    fn test_cycle_bot() {
+        // Paper beats Rock, Rock loses to Paper, Scissors ties with Scissors
+        let stats = play(&[Paper, Rock, Scissors], "cycle:RPS", 6, 3);
+
+        assert_eq!((stats.wins, stats.draws, stats.losses), (6, 6, 6));
+        assert_eq!(stats.scores, vec![30, 30, 30]);
+    }
+
+    #[test]
+    fn test_frequency_bot_exploits_repetitive_player() {
+        let stats = play(&[Rock], "frequency", 100, 1);
+
+        // Every round but the first one is lost to Paper
+        assert!(stats.losses >= 99);
+        assert_eq!(stats.scores, vec![100 + stats.wins * 6 + stats.draws * 3]);
+    }
+
+    #[test]
+    fn test_markov_bot_exploits_cycling_player() {
+        let cycling = play(&[Rock, Paper, Scissors], "markov", 300, 1);
+        let frequency = play(&[Rock, Paper, Scissors], "frequency", 300, 1);
+
+        // After a single cycle of learning, Markov predictor wins every round
+        assert!(cycling.losses >= 296);
+        assert!(frequency.losses < cycling.losses);
+    }
+
+    #[test]
+    fn test_random_bot() {
+        let stats = play(&[Rock, Paper, Scissors], "random", 100, 30);
+        let games = stats.games() as f64;
+
+        for count in [stats.wins, stats.draws, stats.losses] {
+            assert!((count as f64 / games - 1.0 / 3.0).abs() < 0.05);
+        }
+        assert_eq!(stats.scores.len(), 30);
+        assert!(stats.score_percentile(0) <= stats.score_percentile(100));
+    }
+}
@@ -1,5 +1,16 @@
 use anyhow::{bail, Context};
-use common::{get_arg, read_file_to_string};
+use common::{
+    get_arg, get_option, has_flag,
+    profiling::profile,
+    progress::{cancel_on_ctrl_c, get_budget, Progress},
+    read_file_to_string,
+};
+
+mod recovery;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 fn char_to_priority(c: char) -> Result<usize, anyhow::Error> {
     match c {
@@ -9,45 +20,77 @@ fn char_to_priority(c: char) -> Result<usize, anyhow::Error> {
     }
 }
 
-fn split_in_half(s: &str) -> (&str, &str) {
-    let half = s.len() / 2;
+/// Set of item types, with bit `n` set when item of priority `n` is present.
+type ItemSet = u64;
 
-    (&s[0..half], &s[half..])
+fn item_set(items: &str) -> Result<ItemSet, anyhow::Error> {
+    items
+        .chars()
+        .try_fold(0, |set, c| Ok(set | 1 << char_to_priority(c)?))
 }
 
-/// Returns priority label of item common between left and right compartments of
-/// rucksack.
-///
-/// Problem statement guarantees that there always will be only one such item,
-/// so we return as soon as we find it, and not even consider situation where we
-/// cannot.
-///
-/// As number of types of items is known beforehand, and it easily fits on
-/// stack, we use an Boolean array[^1] to represent set of types of items
-/// present in the first compartment, which then is used to find common element
-/// in the second compartment in linear time.
-///
-/// [^1]: We could go lower level with flipping bits in `u64` but there's really
-/// no need for that.
-fn find_common_item_type(rucksack_items: &str) -> Result<usize, anyhow::Error> {
-    // Array of size 53 gives indices between 0 and 52 inclusive. "Phantom" 0-th
-    // item does not matter, as it will never be marked as seen.
-    let mut seen_items = [false; 53];
-
-    let (left, right) = split_in_half(rucksack_items);
+/// Lists priorities of item types in `set`, in ascending order.
+fn priorities(set: ItemSet) -> Vec<usize> {
+    (1..=52)
+        .filter(|priority| set & 1 << priority != 0)
+        .collect()
+}
 
-    for item_priority in left.chars().map(char_to_priority) {
-        seen_items[item_priority?] = true;
+/// Splits rucksack into `count` equally sized compartments.
+fn split_into_compartments(s: &str, count: usize) -> Result<Vec<&str>, anyhow::Error> {
+    // Items are sliced by bytes, so every one of them has to be a single byte
+    if let Some(item) = s.chars().find(|c| !c.is_ascii_alphabetic()) {
+        bail!("invalid item '{}', expected ASCII letter", item);
+    }
+    if count == 0 || !s.len().is_multiple_of(count) {
+        bail!(
+            "can't split {} items into {} equal compartments",
+            s.len(),
+            count
+        );
     }
 
-    for item_priority in right.chars().map(char_to_priority) {
-        let item_priority = item_priority?;
-        if seen_items[item_priority] {
-            return Ok(item_priority);
-        }
+    let size = s.len() / count;
+
+    Ok((0..count).map(|i| &s[i * size..(i + 1) * size]).collect())
+}
+
+/// Returns priorities of all item types present in every one of `item_lists`.
+///
+/// As there are only 52 types of items, sets of them are represented as bits
+/// of `u64`, so finding common ones is just a matter of intersecting them,
+/// which takes linear time in regards to total number of items.
+fn find_common_item_types(item_lists: &[&str]) -> Result<Vec<usize>, anyhow::Error> {
+    let common = item_lists
+        .iter()
+        .map(|items| item_set(items))
+        .try_fold(ItemSet::MAX, |common, set| {
+            Ok::<_, anyhow::Error>(common & set?)
+        })?;
+
+    Ok(priorities(common))
+}
+
+/// Returns priority of the only item type present in every one of
+/// `item_lists`, failing if there are none or many of them.
+fn find_only_common_item_type(item_lists: &[&str]) -> Result<usize, anyhow::Error> {
+    match find_common_item_types(item_lists)?[..] {
+        [priority] => Ok(priority),
+        [] => bail!("no common item type"),
+        ref many => bail!("multiple common item types: {:?}", many),
     }
+}
 
-    unreachable!("every rucksack has common item type between compartments");
+/// Returns priority label of item common between all `compartments` of
+/// rucksack.
+///
+/// Problem statement guarantees that there always will be only one such item,
+/// so anything else is reported as an error.
+fn find_common_item_type(
+    rucksack_items: &str,
+    compartments: usize,
+) -> Result<usize, anyhow::Error> {
+    find_only_common_item_type(&split_into_compartments(rucksack_items, compartments)?)
 }
 
 /// Sums priority labels of items common between compartments of every rucksack
@@ -55,54 +98,53 @@ fn find_common_item_type(rucksack_items: &str) -> Result<usize, anyhow::Error> {
 ///
 /// Every item is "looked at" at most once, so overall run-time complexity is
 /// linear in regards to total number of items in inventories.
-fn sum_common_item_types(inventories_raw: &str) -> Result<usize, anyhow::Error> {
-    let inventories = inventories_raw.lines();
-    inventories.map(find_common_item_type).sum()
+fn sum_common_item_types(
+    inventories_raw: &str,
+    compartments: usize,
+) -> Result<usize, anyhow::Error> {
+    (1..)
+        .zip(inventories_raw.lines())
+        .map(|(line_number, rucksack)| {
+            find_common_item_type(rucksack, compartments)
+                .with_context(|| format!("rucksack on line {}", line_number))
+        })
+        .sum()
 }
 
-/// Returns priority label of item common between three rucksacks.
+/// Returns priority label of item common between all rucksacks of a group.
 ///
 /// Shares properties of [`find_common_item_type()`].
-fn find_common_item_type_between_three_rucksacks(
-    rucksack_a: &str,
-    rucksack_b: &str,
-    rucksack_c: &str,
-) -> Result<usize, anyhow::Error> {
-    let mut seen_in_a = [false; 53];
-    let mut seen_in_a_and_b = [false; 53];
-
-    for item_priority in rucksack_a.chars().map(char_to_priority) {
-        seen_in_a[item_priority?] = true;
-    }
-
-    for item_priority in rucksack_b.chars().map(char_to_priority) {
-        let item_priority = item_priority?;
-        seen_in_a_and_b[item_priority] = seen_in_a[item_priority];
-    }
-
-    for item_priority in rucksack_c.chars().map(char_to_priority) {
-        let item_priority = item_priority?;
-        if seen_in_a_and_b[item_priority] {
-            return Ok(item_priority);
-        }
-    }
-
-    unreachable!("every three consecutive rucksacks have common item type");
+fn find_group_badge(rucksacks: &[&str]) -> Result<usize, anyhow::Error> {
+    find_only_common_item_type(rucksacks)
 }
 
-/// Sums priority labels of items common between every three consecutive
-/// rucksacks.
+/// Sums priority labels of items common between every `group_size`
+/// consecutive rucksacks.
 ///
 /// Every item is "looked at" at most once, so overall run-time complexity is
 /// linear in regards to total number of items in inventories.
-fn sum_group_badges(inventories_raw: &str) -> Result<usize, anyhow::Error> {
-    inventories_raw
-        .lines()
-        .collect::<Vec<_>>()
-        .chunks_exact(3)
-        .map(|chunk| match chunk {
-            &[a, b, c] => find_common_item_type_between_three_rucksacks(a, b, c),
-            _ => unreachable!("`chunks_exact` gives exactly 3-item long slices"),
+fn sum_group_badges(inventories_raw: &str, group_size: usize) -> Result<usize, anyhow::Error> {
+    if group_size == 0 {
+        bail!("group size must be positive");
+    }
+
+    let rucksacks: Vec<&str> = inventories_raw.lines().collect();
+
+    (1..)
+        .step_by(group_size)
+        .zip(rucksacks.chunks(group_size))
+        .map(|(line_number, group)| {
+            if group.len() < group_size {
+                bail!(
+                    "incomplete group starting on line {}: {} of {} rucksacks",
+                    line_number,
+                    group.len(),
+                    group_size
+                );
+            }
+
+            find_group_badge(group)
+                .with_context(|| format!("group starting on line {}", line_number))
         })
         .sum()
 }
@@ -110,9 +152,43 @@ fn sum_group_badges(inventories_raw: &str) -> Result<usize, anyhow::Error> {
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
     let input_string = read_file_to_string(&input_file_path)?;
+    let compartments = match get_option("--compartments") {
+        Some(count) => count.parse().context("couldn't parse --compartments")?,
+        None => 2,
+    };
+    let group_size = match get_option("--group-size") {
+        Some(size) => size.parse().context("couldn't parse --group-size")?,
+        None => 3,
+    };
+
+    if has_flag("--shuffled") {
+        cancel_on_ctrl_c()?;
+        let progress = Progress::hidden(0).with_budget(get_budget()?);
+        let outcome = profile("recovery", || {
+            recovery::recover_badge_groups(&input_string, group_size, &progress)
+        })?;
+        let partial = outcome.is_partial();
+        let recovery = outcome.into_inner();
+
+        let partition = match (recovery.solutions, partial) {
+            (0, false) => "none",
+            (0, true) => "unknown",
+            (1, false) => "unique",
+            (1, true) => "found, uniqueness unknown",
+            _ => "multiple",
+        };
+        println!("Partition: {}", partition);
+        if let Some(badge_sum) = recovery.badge_sum {
+            println!("Badge sum: {}", badge_sum);
+        }
+
+        return Ok(());
+    }
 
-    let part_1_solution = sum_common_item_types(&input_string)?;
-    let part_2_solution = sum_group_badges(&input_string)?;
+    let part_1_solution = profile("part 1", || {
+        sum_common_item_types(&input_string, compartments)
+    })?;
+    let part_2_solution = profile("part 2", || sum_group_badges(&input_string, group_size))?;
 
     println!("Part 1 solution: {}", part_1_solution);
     println!("Part 2 solution: {}", part_2_solution);
@@ -135,39 +211,58 @@ mod tests {
     }
 
     #[test]
-    fn test_split_in_half() {
+    fn test_split_into_compartments() {
+        assert_eq!(
+            split_into_compartments("vJrwpWtwJgWrhcsFMMfFFhFp", 2).unwrap(),
+            vec!["vJrwpWtwJgWr", "hcsFMMfFFhFp"]
+        );
         assert_eq!(
-            split_in_half("vJrwpWtwJgWrhcsFMMfFFhFp"),
-            ("vJrwpWtwJgWr", "hcsFMMfFFhFp")
+            split_into_compartments("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL", 2).unwrap(),
+            vec!["jqHRNqRjqzjGDLGL", "rsFMfFZSrLrFZsSL"]
         );
         assert_eq!(
-            split_in_half("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL"),
-            ("jqHRNqRjqzjGDLGL", "rsFMfFZSrLrFZsSL")
+            split_into_compartments("abcdef", 3).unwrap(),
+            vec!["ab", "cd", "ef"]
         );
+        assert!(split_into_compartments("abc", 2).is_err());
+        assert!(split_into_compartments("aéa", 2).is_err());
     }
 
     #[test]
     fn test_find_common_item() {
         assert_eq!(
-            find_common_item_type("vJrwpWtwJgWrhcsFMMfFFhFp").unwrap(),
+            find_common_item_type("vJrwpWtwJgWrhcsFMMfFFhFp", 2).unwrap(),
             16
         );
         assert_eq!(
-            find_common_item_type("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL").unwrap(),
+            find_common_item_type("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL", 2).unwrap(),
             38
         );
-        assert_eq!(find_common_item_type("PmmdzqPrVvPwwTWBwg").unwrap(), 42);
+        assert_eq!(find_common_item_type("PmmdzqPrVvPwwTWBwg", 2).unwrap(), 42);
         assert_eq!(
-            find_common_item_type("wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn").unwrap(),
+            find_common_item_type("wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn", 2).unwrap(),
             22
         );
-        assert_eq!(find_common_item_type("ttgJtRGJQctTZtZT").unwrap(), 20);
+        assert_eq!(find_common_item_type("ttgJtRGJQctTZtZT", 2).unwrap(), 20);
         assert_eq!(
-            find_common_item_type("CrZsJsPPZsGzwwsLwLmpwMDw").unwrap(),
+            find_common_item_type("CrZsJsPPZsGzwwsLwLmpwMDw", 2).unwrap(),
             19
         );
     }
 
+    #[test]
+    fn test_find_common_item_types() {
+        assert_eq!(
+            find_common_item_types(&["abcA", "cAbz", "Axbc"]).unwrap(),
+            vec![2, 3, 27]
+        );
+        assert_eq!(
+            find_common_item_types(&["ab", "cd"]).unwrap(),
+            Vec::<usize>::new()
+        );
+        assert!(find_common_item_types(&["a1", "a"]).is_err());
+    }
+
     const TEST_INPUT: &str = "\
 vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
@@ -178,26 +273,54 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
 
     #[test]
     fn test_sum_common_item_types() {
-        assert_eq!(sum_common_item_types(&TEST_INPUT).unwrap(), 157);
+        assert_eq!(sum_common_item_types(TEST_INPUT, 2).unwrap(), 157);
     }
 
     #[test]
-    fn test_find_common_item_type_between_three_rucksacks() {
+    fn test_malformed_rucksacks() {
+        let odd = format!("{}\nabc", TEST_INPUT);
+        let err = sum_common_item_types(&odd, 2).unwrap_err();
         assert_eq!(
-            find_common_item_type_between_three_rucksacks(
+            format!("{:#}", err),
+            "rucksack on line 7: can't split 3 items into 2 equal compartments"
+        );
+
+        let err = sum_common_item_types("abcd\nabab", 2).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "rucksack on line 1: no common item type"
+        );
+
+        let err = sum_common_item_types("abab", 2).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "rucksack on line 1: multiple common item types: [1, 2]"
+        );
+
+        let err = sum_common_item_types("abcb\naéaé", 2).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "rucksack on line 2: invalid item 'é', expected ASCII letter"
+        );
+    }
+
+    #[test]
+    fn test_find_group_badge() {
+        assert_eq!(
+            find_group_badge(&[
                 "vJrwpWtwJgWrhcsFMMfFFhFp",
                 "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
                 "PmmdzqPrVvPwwTWBwg"
-            )
+            ])
             .unwrap(),
             18
         );
         assert_eq!(
-            find_common_item_type_between_three_rucksacks(
+            find_group_badge(&[
                 "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
                 "ttgJtRGJQctTZtZT",
                 "CrZsJsPPZsGzwwsLwLmpwMDw"
-            )
+            ])
             .unwrap(),
             52
         );
@@ -205,6 +328,17 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
 
     #[test]
     fn test_sum_group_badges() {
-        assert_eq!(sum_group_badges(&TEST_INPUT).unwrap(), 70);
+        assert_eq!(sum_group_badges(TEST_INPUT, 3).unwrap(), 70);
+    }
+
+    #[test]
+    fn test_sum_group_badges_incomplete_group() {
+        let input = format!("{}\nabc", TEST_INPUT);
+        let err = sum_group_badges(&input, 3).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "incomplete group starting on line 7: 1 of 3 rucksacks"
+        );
     }
 }
@@ -0,0 +1,283 @@
+//! Recovering badge groups from rucksacks in unknown order.
+
+use anyhow::{bail, Context};
+use common::progress::{Outcome, Progress};
+
+use crate::{find_group_badge, item_set, ItemSet};
+
+/// Result of searching for partitions of rucksacks into badge groups.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recovery {
+    /// First partition found, as groups of indices of rucksacks
+    pub groups: Option<Vec<Vec<usize>>>,
+    /// Number of partitions found, counting stops at 2
+    pub solutions: usize,
+    /// Sum of badge priorities of the first partition found
+    pub badge_sum: Option<usize>,
+}
+
+/// Enumerates all groups of `group_size` rucksacks sharing exactly one item
+/// type, as indices of their members.
+fn find_candidate_groups(sets: &[ItemSet], group_size: usize) -> Vec<Vec<usize>> {
+    fn extend(
+        sets: &[ItemSet],
+        group_size: usize,
+        group: &mut Vec<usize>,
+        common: ItemSet,
+        groups: &mut Vec<Vec<usize>>,
+    ) {
+        if group.len() == group_size {
+            if common.count_ones() == 1 {
+                groups.push(group.clone());
+            }
+            return;
+        }
+
+        let start = group.last().map_or(0, |&last| last + 1);
+        for i in start..sets.len() {
+            // Adding members can only shrink set of common item types
+            let common = common & sets[i];
+            if common != 0 {
+                group.push(i);
+                extend(sets, group_size, group, common, groups);
+                group.pop();
+            }
+        }
+    }
+
+    let mut groups = vec![];
+    extend(sets, group_size, &mut vec![], ItemSet::MAX, &mut groups);
+
+    groups
+}
+
+/// Exact cover search over candidate groups (Knuth's Algorithm X), always
+/// branching on the rucksack with the fewest candidate groups left.
+struct Search<'a> {
+    groups: Vec<Vec<usize>>,
+    /// Indices of candidate groups every rucksack belongs to
+    groups_of: Vec<Vec<usize>>,
+    /// Number of chosen groups overlapping every candidate group, which is
+    /// still available only when that's 0
+    blocked: Vec<usize>,
+    /// Number of available candidate groups of every rucksack
+    available: Vec<usize>,
+    covered: Vec<bool>,
+    chosen: Vec<usize>,
+    progress: &'a Progress,
+    solutions: usize,
+    first_solution: Option<Vec<Vec<usize>>>,
+}
+
+impl Search<'_> {
+    fn cover(&mut self, rucksack: usize) {
+        self.covered[rucksack] = true;
+
+        for &group in &self.groups_of[rucksack] {
+            if self.blocked[group] == 0 {
+                for &member in &self.groups[group] {
+                    self.available[member] -= 1;
+                }
+            }
+            self.blocked[group] += 1;
+        }
+    }
+
+    fn uncover(&mut self, rucksack: usize) {
+        for &group in &self.groups_of[rucksack] {
+            self.blocked[group] -= 1;
+            if self.blocked[group] == 0 {
+                for &member in &self.groups[group] {
+                    self.available[member] += 1;
+                }
+            }
+        }
+
+        self.covered[rucksack] = false;
+    }
+
+    fn run(&mut self) {
+        if self.solutions >= 2 || self.progress.should_stop() {
+            return;
+        }
+
+        let Some(rucksack) = (0..self.covered.len())
+            .filter(|&r| !self.covered[r])
+            .min_by_key(|&r| self.available[r])
+        else {
+            self.solutions += 1;
+            if self.first_solution.is_none() {
+                let groups = self.chosen.iter().map(|&g| self.groups[g].clone());
+                self.first_solution = Some(groups.collect());
+            }
+            return;
+        };
+
+        let candidates: Vec<usize> = self.groups_of[rucksack]
+            .iter()
+            .copied()
+            .filter(|&group| self.blocked[group] == 0)
+            .collect();
+
+        for group in candidates {
+            let members = self.groups[group].clone();
+            for &member in &members {
+                self.cover(member);
+            }
+            self.chosen.push(group);
+
+            self.run();
+
+            self.chosen.pop();
+            for &member in members.iter().rev() {
+                self.uncover(member);
+            }
+
+            if self.solutions >= 2 || self.progress.was_stopped() {
+                return;
+            }
+        }
+    }
+}
+
+/// Partitions shuffled rucksacks into groups of `group_size`, each sharing
+/// exactly one item type, with exact cover search. Search stops after finding
+/// a second partition, as that's enough to tell it's not unique.
+///
+/// Returns [`Outcome::Partial`] when `progress` stopped the search early, in
+/// which case existence or uniqueness of a partition is unknown.
+pub fn recover_badge_groups(
+    inventories_raw: &str,
+    group_size: usize,
+    progress: &Progress,
+) -> Result<Outcome<Recovery>, anyhow::Error> {
+    let rucksacks: Vec<&str> = inventories_raw.lines().collect();
+
+    if group_size == 0 || !rucksacks.len().is_multiple_of(group_size) {
+        bail!(
+            "can't split {} rucksacks into groups of {}",
+            rucksacks.len(),
+            group_size
+        );
+    }
+
+    let sets = (1..)
+        .zip(&rucksacks)
+        .map(|(line_number, rucksack)| {
+            item_set(rucksack).with_context(|| format!("rucksack on line {}", line_number))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let groups = find_candidate_groups(&sets, group_size);
+    let mut groups_of = vec![vec![]; sets.len()];
+    for (i, group) in groups.iter().enumerate() {
+        for &member in group {
+            groups_of[member].push(i);
+        }
+    }
+
+    let mut search = Search {
+        blocked: vec![0; groups.len()],
+        available: groups_of.iter().map(Vec::len).collect(),
+        covered: vec![false; sets.len()],
+        chosen: vec![],
+        groups,
+        groups_of,
+        progress,
+        solutions: 0,
+        first_solution: None,
+    };
+    search.run();
+
+    let badge_sum = search
+        .first_solution
+        .as_ref()
+        .map(|groups| {
+            groups
+                .iter()
+                .map(|group| {
+                    let members: Vec<&str> = group.iter().map(|&i| rucksacks[i]).collect();
+
+                    find_group_badge(&members)
+                })
+                .sum::<Result<usize, _>>()
+        })
+        .transpose()?;
+
+    Ok(progress.finish(Recovery {
+        groups: search.first_solution,
+        solutions: search.solutions,
+        badge_sum,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const SHUFFLED_INPUT: &str = "\
+CrZsJsPPZsGzwwsLwLmpwMDw
+vJrwpWtwJgWrhcsFMMfFFhFp
+ttgJtRGJQctTZtZT
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+PmmdzqPrVvPwwTWBwg";
+
+    #[test]
+    fn test_recover_badge_groups() {
+        let recovery = recover_badge_groups(SHUFFLED_INPUT, 3, &Progress::hidden(0))
+            .unwrap()
+            .into_inner();
+
+        // Besides original groups (with badges r and Z), rucksacks can also be
+        // grouped by badges J and q
+        assert_eq!(recovery.solutions, 2);
+        let mut groups = recovery.groups.unwrap();
+        groups.sort();
+        assert_eq!(groups, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        assert_eq!(recovery.badge_sum, Some(36 + 17));
+    }
+
+    #[test]
+    fn test_recover_badge_groups_unique() {
+        let recovery = recover_badge_groups("cd\nbc\nde\nab", 2, &Progress::hidden(0))
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(recovery.solutions, 1);
+        assert_eq!(recovery.groups, Some(vec![vec![0, 2], vec![1, 3]]));
+        assert_eq!(recovery.badge_sum, Some(2 + 4));
+    }
+
+    #[test]
+    fn test_recover_badge_groups_ambiguous() {
+        // Any pair of rucksacks shares exactly one item type
+        let recovery = recover_badge_groups("ab\nac\nad\naf", 2, &Progress::hidden(0))
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(recovery.solutions, 2);
+        assert_eq!(recovery.badge_sum, Some(2));
+    }
+
+    #[test]
+    fn test_recover_badge_groups_impossible() {
+        let recovery = recover_badge_groups("ab\ncd\nae\nbf", 2, &Progress::hidden(0))
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(recovery.solutions, 0);
+        assert_eq!(recovery.groups, None);
+        assert!(recover_badge_groups("ab\ncd\nae", 2, &Progress::hidden(0)).is_err());
+    }
+
+    #[test]
+    fn test_recover_badge_groups_out_of_budget() {
+        let progress = Progress::hidden(0).with_budget(Some(Duration::ZERO));
+        let recovery = recover_badge_groups(SHUFFLED_INPUT, 3, &progress).unwrap();
+
+        assert!(recovery.is_partial());
+    }
+}
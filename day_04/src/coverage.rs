@@ -0,0 +1,307 @@
+//! Camp-wide analysis of section coverage across all assignments, with a sweep
+//! line over range boundaries.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+/// Elf identified by index of its line and its position within that line.
+pub type ElfId = (usize, usize);
+
+/// Maximal run of sections covered by the same number of elves (start- and
+/// end-points inclusive).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub from: u64,
+    pub to: u64,
+    pub elves: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coverage {
+    /// Segments spanning all sections from the lowest to the highest assigned
+    /// one, in order
+    pub segments: Vec<Segment>,
+    /// Elves whose whole range is covered by the ranges of other elves
+    pub redundant: Vec<ElfId>,
+    /// Smallest set of elves covering every assigned section
+    pub minimum_cover: Vec<ElfId>,
+}
+
+/// Splits sections into segments covered by the same number of ranges, by
+/// sweeping over points where ranges start and end.
+fn sweep(ranges: &[[u64; 2]]) -> Vec<Segment> {
+    let mut deltas: BTreeMap<u64, isize> = BTreeMap::new();
+    for &[start, end] in ranges {
+        *deltas.entry(start).or_default() += 1;
+        // Range reaching the last section never ends
+        if let Some(after_end) = end.checked_add(1) {
+            *deltas.entry(after_end).or_default() -= 1;
+        }
+    }
+
+    let mut segments = vec![];
+    let mut elves = 0;
+    let mut from = None;
+    // Ranges ending right before others start don't change coverage
+    for (position, delta) in deltas.into_iter().filter(|&(_, delta)| delta != 0) {
+        if let Some(from) = from {
+            segments.push(Segment {
+                from,
+                to: position - 1,
+                elves: elves as usize,
+            });
+        }
+
+        elves += delta;
+        from = Some(position);
+    }
+
+    if let Some(from) = from.filter(|_| elves > 0) {
+        segments.push(Segment {
+            from,
+            to: u64::MAX,
+            elves: elves as usize,
+        });
+    }
+
+    segments
+}
+
+/// Finds indices of ranges which are fully covered by other ranges, i.e. lie
+/// within segments covered by at least two ranges.
+fn find_redundant(ranges: &[[u64; 2]], segments: &[Segment]) -> Vec<usize> {
+    // Number of segments covered by a single range before every segment
+    let mut single = vec![0; segments.len() + 1];
+    for (i, segment) in segments.iter().enumerate() {
+        single[i + 1] = single[i] + usize::from(segment.elves == 1);
+    }
+
+    let segment_of = |section: u64| segments.partition_point(|segment| segment.to < section);
+
+    (0..ranges.len())
+        .filter(|&i| {
+            let [start, end] = ranges[i];
+
+            single[segment_of(end) + 1] == single[segment_of(start)]
+        })
+        .collect()
+}
+
+/// Finds indices of the smallest set of ranges covering the same sections as
+/// all ranges together, by repeatedly picking the range reaching the farthest
+/// among those starting at or before the first section not covered yet.
+fn find_minimum_cover(ranges: &[[u64; 2]]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by_key(|&i| ranges[i]);
+
+    let mut cover = vec![];
+    let mut next = 0;
+    let mut section = 0;
+    let mut best: Option<usize> = None;
+
+    loop {
+        while next < order.len() && ranges[order[next]][0] <= section {
+            let candidate = order[next];
+            if best.is_none_or(|best| ranges[candidate][1] > ranges[best][1]) {
+                best = Some(candidate);
+            }
+            next += 1;
+        }
+
+        match best.take() {
+            Some(best) if ranges[best][1] >= section => {
+                cover.push(best);
+                match ranges[best][1].checked_add(1) {
+                    Some(after_end) => section = after_end,
+                    None => break,
+                }
+            }
+            // Gap between assignments, skip to the next range
+            _ if next < order.len() => section = ranges[order[next]][0],
+            _ => break,
+        }
+    }
+
+    cover.sort_unstable();
+    cover
+}
+
+impl Coverage {
+    /// Analyzes coverage of sections by ranges of elves, listed line by line.
+    pub fn new(lines: &[Vec<[u64; 2]>]) -> Self {
+        let elves: Vec<ElfId> = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(line, ranges)| (0..ranges.len()).map(move |position| (line, position)))
+            .collect();
+        let ranges: Vec<[u64; 2]> = lines.concat();
+
+        let segments = sweep(&ranges);
+        let redundant = find_redundant(&ranges, &segments)
+            .into_iter()
+            .map(|i| elves[i])
+            .collect();
+        let minimum_cover = find_minimum_cover(&ranges)
+            .into_iter()
+            .map(|i| elves[i])
+            .collect();
+
+        Self {
+            segments,
+            redundant,
+            minimum_cover,
+        }
+    }
+
+    /// Runs of assigned sections nobody is assigned to.
+    pub fn uncovered(&self) -> Vec<[u64; 2]> {
+        self.segments_covered_by(0)
+    }
+
+    /// Largest number of elves assigned to a single section.
+    pub fn max_elves(&self) -> usize {
+        self.segments.iter().map(|s| s.elves).max().unwrap_or(0)
+    }
+
+    /// Runs of sections assigned to the largest number of elves.
+    pub fn most_covered(&self) -> Vec<[u64; 2]> {
+        self.segments_covered_by(self.max_elves())
+    }
+
+    fn segments_covered_by(&self, elves: usize) -> Vec<[u64; 2]> {
+        self.segments
+            .iter()
+            .filter(|segment| segment.elves == elves)
+            .map(|segment| [segment.from, segment.to])
+            .collect()
+    }
+}
+
+fn format_sections(sections: &[[u64; 2]]) -> String {
+    if sections.is_empty() {
+        return "none".to_string();
+    }
+
+    sections
+        .iter()
+        .map(|&[from, to]| match from == to {
+            true => from.to_string(),
+            false => format!("{}-{}", from, to),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_elves(elves: &[ElfId]) -> String {
+    if elves.is_empty() {
+        return "none".to_string();
+    }
+
+    elves
+        .iter()
+        .map(|(line, position)| format!("{}:{}", line + 1, position + 1))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (Some(first), Some(last)) = (self.segments.first(), self.segments.last()) else {
+            return writeln!(f, "No sections assigned");
+        };
+
+        writeln!(f, "Sections: {}-{}", first.from, last.to)?;
+        writeln!(f, "Uncovered: {}", format_sections(&self.uncovered()))?;
+        writeln!(
+            f,
+            "Most covered ({} elves): {}",
+            self.max_elves(),
+            format_sections(&self.most_covered())
+        )?;
+        writeln!(
+            f,
+            "Redundant elves ({}, as line:elf): {}",
+            self.redundant.len(),
+            format_elves(&self.redundant)
+        )?;
+        writeln!(
+            f,
+            "Minimum cover ({} elves, as line:elf): {}",
+            self.minimum_cover.len(),
+            format_elves(&self.minimum_cover)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Vec<[u64; 2]>> {
+        vec![
+            vec![[2, 4], [6, 8]],
+            vec![[2, 3], [4, 5]],
+            vec![[5, 7], [7, 9]],
+            vec![[2, 8], [3, 7]],
+            vec![[6, 6], [4, 6]],
+            vec![[2, 6], [4, 8]],
+        ]
+    }
+
+    #[test]
+    fn test_coverage() {
+        let coverage = Coverage::new(&sample());
+
+        let elves: Vec<usize> = coverage.segments.iter().map(|s| s.elves).collect();
+        assert_eq!(elves, vec![4, 5, 7, 8, 6, 4, 1]);
+        assert_eq!(
+            coverage.segments[2],
+            Segment {
+                from: 4,
+                to: 5,
+                elves: 7
+            }
+        );
+        assert!(coverage.uncovered().is_empty());
+        assert_eq!(coverage.most_covered(), vec![[6, 6]]);
+        assert_eq!(coverage.max_elves(), 8);
+
+        // Only the elf assigned to 7-9 covers section 9
+        assert_eq!(coverage.redundant.len(), 11);
+        assert!(!coverage.redundant.contains(&(2, 1)));
+        assert_eq!(coverage.minimum_cover, vec![(2, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn test_coverage_with_gaps() {
+        let coverage = Coverage::new(&[vec![[1, 3], [2, 5]], vec![[8, 9], [4, 4]]]);
+
+        assert_eq!(coverage.uncovered(), vec![[6, 7]]);
+        assert_eq!(coverage.most_covered(), vec![[2, 4]]);
+        assert_eq!(coverage.redundant, vec![(1, 1)]);
+        assert_eq!(coverage.minimum_cover, vec![(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(
+            coverage.to_string(),
+            "\
+Sections: 1-9
+Uncovered: 6-7
+Most covered (2 elves): 2-4
+Redundant elves (1, as line:elf): 2:2
+Minimum cover (3 elves, as line:elf): 1:1, 1:2, 2:1
+"
+        );
+    }
+
+    #[test]
+    fn test_coverage_identical_and_boundary_ranges() {
+        // Identical ranges cover each other, yet one of them is needed
+        let coverage = Coverage::new(&[
+            vec![[u64::MAX - 1, u64::MAX], [u64::MAX - 1, u64::MAX]],
+            vec![[0, 0]],
+        ]);
+
+        assert_eq!(coverage.uncovered(), vec![[1, u64::MAX - 2]]);
+        assert_eq!(coverage.segments.last().unwrap().to, u64::MAX);
+        assert_eq!(coverage.redundant, vec![(0, 0), (0, 1)]);
+        assert_eq!(coverage.minimum_cover, vec![(0, 0), (1, 0)]);
+    }
+}
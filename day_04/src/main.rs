@@ -1,57 +1,86 @@
-use std::str::FromStr;
+use std::{fmt::Debug, num::ParseIntError, str::FromStr};
 
-use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+use anyhow::{anyhow, bail, Context};
+#[cfg(not(feature = "serde"))]
+use common::read_problem;
+#[cfg(feature = "serde")]
+use common::serialization::read_problem;
+use common::{get_arg, get_option, has_flag, profiling::profile};
 
-type AssignmentPair = ([u8; 2], [u8; 2]);
+mod coverage;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
+
+/// Unsigned integer type section IDs are stored as, up to `u64`.
+trait SectionId: Copy + Ord + Debug + FromStr<Err = ParseIntError> + Into<u64> {}
+
+impl<T> SectionId for T where T: Copy + Ord + Debug + FromStr<Err = ParseIntError> + Into<u64> {}
+
+/// Start and end section of an assignment (both inclusive).
+type Assignment<T> = [T; 2];
+
+/// Assignments of all elves listed on a single line.
+type AssignmentGroup<T> = Vec<Assignment<T>>;
 
 #[derive(Debug)]
-struct Problem {
-    assignment_pairs: Vec<AssignmentPair>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Problem<T> {
+    assignment_groups: Vec<AssignmentGroup<T>>,
 }
 
-impl FromStr for Problem {
+impl<T: SectionId> FromStr for Problem<T> {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let assignment_pairs = s
-            .lines()
-            .map(|line| {
-                let (fst, snd) = line
-                    .split_once(',')
-                    .ok_or_else(|| anyhow!("couldn't split '{}' at ','", s))?;
-
-                let (fst_start, fst_end) = fst
-                    .split_once('-')
-                    .ok_or_else(|| anyhow!("couldn't split '{}' at '-'", fst))?;
-
-                let (snd_start, snd_end) = snd
-                    .split_once('-')
-                    .ok_or_else(|| anyhow!("couldn't split '{}' at '-'", snd))?;
-
-                Ok::<_, Self::Err>((
-                    [fst_start.parse()?, fst_end.parse()?],
-                    [snd_start.parse()?, snd_end.parse()?],
-                ))
+        let assignment_groups = (1..)
+            .zip(s.lines())
+            .map(|(line_number, line)| {
+                line.split(',')
+                    .map(|assignment| {
+                        let (start, end) = assignment
+                            .split_once('-')
+                            .ok_or_else(|| anyhow!("couldn't split '{}' at '-'", assignment))?;
+                        let sections = [
+                            start
+                                .parse()
+                                .with_context(|| format!("couldn't parse '{}'", start))?,
+                            end.parse()
+                                .with_context(|| format!("couldn't parse '{}'", end))?,
+                        ];
+
+                        if sections[0] > sections[1] {
+                            bail!("assignment '{}' ends before it starts", assignment);
+                        }
+
+                        Ok(sections)
+                    })
+                    .collect::<Result<_, _>>()
+                    .with_context(|| format!("assignments on line {}", line_number))
             })
             .collect::<Result<_, _>>()?;
 
-        Ok(Problem { assignment_pairs })
+        Ok(Problem { assignment_groups })
     }
 }
 
-/// Checks if either range is fully contained in the other (start- and
-/// end-points inclusive).
-fn assignments_fully_overlap(assignment_pair: &AssignmentPair) -> bool {
-    let ([fst_start, fst_end], [snd_start, snd_end]) = assignment_pair;
+/// Checks if one of the ranges contains all others (start- and end-points
+/// inclusive).
+fn assignments_fully_overlap<T: SectionId>(group: &[Assignment<T>]) -> bool {
+    let (Some(start), Some(end)) = (
+        group.iter().map(|[start, _]| start).min(),
+        group.iter().map(|[_, end]| end).max(),
+    ) else {
+        return false;
+    };
 
-    (fst_start <= snd_start && snd_end <= fst_end) || (snd_start <= fst_start && fst_end <= snd_end)
+    group.contains(&[*start, *end])
 }
 
-/// Checks if any range limit falls within limits of the other range (start- and
-/// end-points inclusive).
+/// Checks if any pair of ranges overlaps (start- and end-points inclusive).
 ///
-/// When ranges overlap, they do so in one of three ways:
+/// Two ranges overlap in one of three ways:
 ///
 /// ```text
 ///     ↓¹
@@ -70,45 +99,125 @@ fn assignments_fully_overlap(assignment_pair: &AssignmentPair) -> bool {
 /// Therefore, ranges overlap if at least one of the following is true: (1)
 /// start-point of the first range falls within second range, (2) start-point of
 /// the second range falls within first range. Third case folds into either
-/// first or second, when ranges are inclusive.
-fn assignments_partially_overlap(assignment_pair: &AssignmentPair) -> bool {
-    let ([fst_start, fst_end], [snd_start, snd_end]) = assignment_pair;
+/// first or second, when ranges are inclusive. With ranges ordered by their
+/// start-points, the first overlap found is always between neighbours, as
+/// ranges before it are disjoint.
+fn assignments_partially_overlap<T: SectionId>(group: &[Assignment<T>]) -> bool {
+    let mut group = group.to_vec();
+    group.sort_unstable();
+
+    group.windows(2).any(|pair| pair[1][0] <= pair[0][1])
+}
+
+/// Checks if there's a section within all ranges.
+fn assignments_share_section<T: SectionId>(group: &[Assignment<T>]) -> bool {
+    let (Some(start), Some(end)) = (
+        group.iter().map(|[start, _]| start).max(),
+        group.iter().map(|[_, end]| end).min(),
+    ) else {
+        return false;
+    };
+
+    start <= end
+}
 
-    (snd_start <= fst_start && fst_start <= snd_end)
-        || (fst_start <= snd_start && snd_start <= fst_end)
+/// What makes a group of assignments count as partially overlapping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PartialOverlap {
+    /// Any pair of ranges overlaps
+    AnyPair,
+    /// All ranges share at least one section
+    Common,
 }
 
-fn count_fully_overlapping_assignments(assignment_pairs: &[AssignmentPair]) -> usize {
-    assignment_pairs
+impl FromStr for PartialOverlap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(Self::AnyPair),
+            "common" => Ok(Self::Common),
+            _ => bail!("unknown overlap: {} (expected 'any' or 'common')", s),
+        }
+    }
+}
+
+fn count_fully_overlapping_assignments<T: SectionId>(
+    assignment_groups: &[AssignmentGroup<T>],
+) -> usize {
+    assignment_groups
         .iter()
         .filter(|a| assignments_fully_overlap(a))
         .count()
 }
 
-fn count_partially_overlapping_assignments(assignment_pairs: &[AssignmentPair]) -> usize {
-    assignment_pairs
+fn count_partially_overlapping_assignments<T: SectionId>(
+    assignment_groups: &[AssignmentGroup<T>],
+    overlap: PartialOverlap,
+) -> usize {
+    assignment_groups
         .iter()
-        .filter(|a| assignments_partially_overlap(a))
+        .filter(|a| match overlap {
+            PartialOverlap::AnyPair => assignments_partially_overlap(a),
+            PartialOverlap::Common => assignments_share_section(a),
+        })
         .count()
 }
 
-fn main() -> Result<(), anyhow::Error> {
-    let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
-    let input_string = read_file_to_string(&input_file_path)?;
-    let Problem { assignment_pairs } = input_string.parse()?;
+fn solve<T: SectionId>(problem: Problem<T>) -> Result<(), anyhow::Error> {
+    let Problem { assignment_groups } = problem;
+
+    if has_flag("--coverage") {
+        let lines: Vec<Vec<[u64; 2]>> = assignment_groups
+            .iter()
+            .map(|group| group.iter().map(|a| a.map(Into::into)).collect())
+            .collect();
+        print!(
+            "{}",
+            profile("coverage", || coverage::Coverage::new(&lines))
+        );
+
+        return Ok(());
+    }
+
+    let overlap = match get_option("--overlap") {
+        Some(overlap) => overlap.parse().context("couldn't parse --overlap")?,
+        None => PartialOverlap::AnyPair,
+    };
 
     println!(
         "Part 1 solution: {}",
-        count_fully_overlapping_assignments(&assignment_pairs)
+        profile("part 1", || count_fully_overlapping_assignments(
+            &assignment_groups
+        ))
     );
     println!(
         "Part 2 solution: {}",
-        count_partially_overlapping_assignments(&assignment_pairs)
+        profile("part 2", || count_partially_overlapping_assignments(
+            &assignment_groups,
+            overlap
+        ))
     );
 
     Ok(())
 }
 
+fn main() -> Result<(), anyhow::Error> {
+    let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
+    let width = match get_option("--width") {
+        Some(width) => width.parse().context("couldn't parse --width")?,
+        None => 64,
+    };
+
+    match width {
+        8 => solve(read_problem::<Problem<u8>>(&input_file_path)?),
+        16 => solve(read_problem::<Problem<u16>>(&input_file_path)?),
+        32 => solve(read_problem::<Problem<u32>>(&input_file_path)?),
+        64 => solve(read_problem::<Problem<u64>>(&input_file_path)?),
+        _ => bail!("unsupported --width: {} (expected 8, 16, 32 or 64)", width),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,37 +230,83 @@ mod tests {
 6-6,4-6
 2-6,4-8";
 
+    const GROUPS_INPUT: &str = "\
+1-10,2-3,5-6
+1-3,3-5,5-7
+1-5,2-6,4-8
+1-1,3-3,5-5";
+
     #[test]
     fn test_problem_parse() {
-        let Problem { assignment_pairs } = TEST_INPUT.parse().unwrap();
+        let Problem { assignment_groups } = TEST_INPUT.parse::<Problem<u8>>().unwrap();
 
         assert_eq!(
-            assignment_pairs,
+            assignment_groups,
             vec![
-                ([2, 4], [6, 8]),
-                ([2, 3], [4, 5]),
-                ([5, 7], [7, 9]),
-                ([2, 8], [3, 7]),
-                ([6, 6], [4, 6]),
-                ([2, 6], [4, 8]),
+                vec![[2, 4], [6, 8]],
+                vec![[2, 3], [4, 5]],
+                vec![[5, 7], [7, 9]],
+                vec![[2, 8], [3, 7]],
+                vec![[6, 6], [4, 6]],
+                vec![[2, 6], [4, 8]],
             ]
         );
     }
 
+    #[test]
+    fn test_problem_parse_wide() {
+        let input = "300-400,350-1000,1-18446744073709551615";
+
+        assert!(input.parse::<Problem<u8>>().is_err());
+        assert!(input.parse::<Problem<u16>>().is_err());
+        let Problem { assignment_groups } = input.parse::<Problem<u64>>().unwrap();
+        assert_eq!(
+            assignment_groups,
+            vec![vec![[300, 400], [350, 1000], [1, u64::MAX]]]
+        );
+
+        assert!("5-4,1-2".parse::<Problem<u64>>().is_err());
+        let err = "1-2,3-4\n1-2,5-4".parse::<Problem<u64>>().unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "assignments on line 2: assignment '5-4' ends before it starts"
+        );
+        assert!("1-2,".parse::<Problem<u64>>().is_err());
+    }
+
     #[test]
     fn test_count_fully_overlapping_assignments() {
-        let Problem { assignment_pairs } = TEST_INPUT.parse().unwrap();
+        let Problem { assignment_groups } = TEST_INPUT.parse::<Problem<u8>>().unwrap();
+        assert_eq!(count_fully_overlapping_assignments(&assignment_groups), 2);
 
-        assert_eq!(count_fully_overlapping_assignments(&assignment_pairs), 2);
+        let Problem { assignment_groups } = GROUPS_INPUT.parse::<Problem<u32>>().unwrap();
+        assert_eq!(count_fully_overlapping_assignments(&assignment_groups), 1);
     }
 
     #[test]
     fn test_count_partially_overlapping_assignments() {
-        let Problem { assignment_pairs } = TEST_INPUT.parse().unwrap();
+        let Problem { assignment_groups } = TEST_INPUT.parse::<Problem<u8>>().unwrap();
 
+        // For pairs both ways of counting agree
+        for overlap in [PartialOverlap::AnyPair, PartialOverlap::Common] {
+            assert_eq!(
+                count_partially_overlapping_assignments(&assignment_groups, overlap),
+                4
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_partially_overlapping_groups() {
+        let Problem { assignment_groups } = GROUPS_INPUT.parse::<Problem<u32>>().unwrap();
+
+        assert_eq!(
+            count_partially_overlapping_assignments(&assignment_groups, PartialOverlap::AnyPair),
+            3
+        );
         assert_eq!(
-            count_partially_overlapping_assignments(&assignment_pairs),
-            4
+            count_partially_overlapping_assignments(&assignment_groups, PartialOverlap::Common),
+            1
         );
     }
 }
@@ -0,0 +1,323 @@
+//! Crane models, which differ in how they carry crates between stacks and how
+//! much that costs.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::Instruction;
+
+/// Lifts crates from the top of stacks and puts them down on other stacks.
+pub trait Crane {
+    /// Executes a single instruction on stacks, returning its cost.
+    fn execute(
+        &self,
+        stacks: &mut [Vec<char>],
+        instruction: &Instruction,
+    ) -> Result<u64, anyhow::Error>;
+}
+
+/// Takes `num` crates from the top of stack `from` at once, keeping their
+/// order.
+fn lift(stacks: &mut [Vec<char>], from: usize, num: usize) -> Result<Vec<char>, anyhow::Error> {
+    let idx_to_pick_up_from = stacks[from]
+        .len()
+        .checked_sub(num)
+        .ok_or_else(|| anyhow!("stack {} has less than {} items left on it", from, num))?;
+
+    Ok(stacks[from].split_off(idx_to_pick_up_from))
+}
+
+/// CrateMover 9000, picking up a single crate at a time. Every crate moved
+/// costs 1.
+pub struct SinglePickUp;
+
+impl Crane for SinglePickUp {
+    fn execute(
+        &self,
+        stacks: &mut [Vec<char>],
+        instruction: &Instruction,
+    ) -> Result<u64, anyhow::Error> {
+        let Instruction { num, from, to } = *instruction;
+
+        // Move items one by one. One could re-use loop-less solution from
+        // [`MultiPickUp`], by just reversing the order of lifted items, but
+        // this way the intention is much clearer
+        for _ in 0..num {
+            let v = stacks[from]
+                .pop()
+                .ok_or_else(|| anyhow!("no items left on stack {}", from))?;
+            stacks[to].push(v);
+        }
+
+        Ok(num as u64)
+    }
+}
+
+/// CrateMover 9001, picking up any number of crates at once. Every
+/// instruction costs 1.
+pub struct MultiPickUp;
+
+impl Crane for MultiPickUp {
+    fn execute(
+        &self,
+        stacks: &mut [Vec<char>],
+        instruction: &Instruction,
+    ) -> Result<u64, anyhow::Error> {
+        let Instruction { num, from, to } = *instruction;
+
+        // move `num` items from the end of stack at once
+        let mut v = lift(stacks, from, num)?;
+        stacks[to].append(&mut v);
+
+        Ok(1)
+    }
+}
+
+/// How a crane turns crates it lifts before putting them down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Flip {
+    /// Crates keep their order
+    None,
+    /// Whole lift is turned upside down
+    Reverse,
+    /// Only the given number of the topmost crates of a lift is turned upside
+    /// down, the rest keeps its order
+    Top(usize),
+}
+
+impl Flip {
+    fn apply(&self, lifted: &mut [char]) {
+        let flipped = match *self {
+            Self::None => 0,
+            Self::Reverse => lifted.len(),
+            Self::Top(n) => n.min(lifted.len()),
+        };
+        let first_flipped = lifted.len() - flipped;
+
+        lifted[first_flipped..].reverse();
+    }
+}
+
+/// Crane with configurable capacity, flipping of lifts and costs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigurableCrane {
+    /// Maximum number of crates in a single lift, moves of more crates are
+    /// split into multiple lifts, topmost crates first
+    pub capacity: Option<usize>,
+    pub flip: Flip,
+    /// Cost of every lift
+    pub lift_cost: u64,
+    /// Cost of every crate moved
+    pub crate_cost: u64,
+}
+
+impl Default for ConfigurableCrane {
+    /// Crane which works just like [`MultiPickUp`].
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            flip: Flip::None,
+            lift_cost: 1,
+            crate_cost: 0,
+        }
+    }
+}
+
+impl Crane for ConfigurableCrane {
+    fn execute(
+        &self,
+        stacks: &mut [Vec<char>],
+        instruction: &Instruction,
+    ) -> Result<u64, anyhow::Error> {
+        let Instruction { num, from, to } = *instruction;
+
+        // Check up front, so that a failing instruction doesn't leave stacks
+        // with only some of the lifts done
+        if stacks[from].len() < num {
+            bail!("stack {} has less than {} items left on it", from, num);
+        }
+
+        let mut remaining = num;
+        let mut cost = 0;
+        while remaining > 0 {
+            let lifted_count = remaining.min(self.capacity.unwrap_or(usize::MAX));
+            let mut lifted = lift(stacks, from, lifted_count)?;
+            self.flip.apply(&mut lifted);
+            stacks[to].append(&mut lifted);
+
+            cost += self.lift_cost + self.crate_cost * lifted_count as u64;
+            remaining -= lifted_count;
+        }
+
+        Ok(cost)
+    }
+}
+
+impl FromStr for ConfigurableCrane {
+    type Err = anyhow::Error;
+
+    /// Parses comma-separated `key=value` settings, with keys `capacity`,
+    /// `flip` (`none`, `reverse` or `top:N`), `lift-cost` and `crate-cost`,
+    /// e.g. `capacity=3,flip=top:2,crate-cost=1`. Missing settings are taken
+    /// from [`ConfigurableCrane::default`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut crane = Self::default();
+
+        for setting in s.split(',').filter(|setting| !setting.is_empty()) {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| anyhow!("couldn't split '{}' at '='", setting))?;
+
+            match key {
+                "capacity" => {
+                    let capacity = value.parse().context("couldn't parse capacity")?;
+                    if capacity == 0 {
+                        bail!("capacity must be positive");
+                    }
+                    crane.capacity = Some(capacity);
+                }
+                "flip" => {
+                    crane.flip = match value {
+                        "none" => Flip::None,
+                        "reverse" => Flip::Reverse,
+                        _ => {
+                            let n = value
+                                .strip_prefix("top:")
+                                .ok_or_else(|| anyhow!("unknown flip: {}", value))?;
+
+                            Flip::Top(n.parse().context("couldn't parse flip")?)
+                        }
+                    }
+                }
+                "lift-cost" => {
+                    crane.lift_cost = value.parse().context("couldn't parse lift-cost")?
+                }
+                "crate-cost" => {
+                    crane.crate_cost = value.parse().context("couldn't parse crate-cost")?
+                }
+                _ => bail!("unknown crane setting: {}", key),
+            }
+        }
+
+        Ok(crane)
+    }
+}
+
+/// Parses crane given as `9000` ([`SinglePickUp`]), `9001` ([`MultiPickUp`]),
+/// or settings of [`ConfigurableCrane`].
+pub fn parse_crane(s: &str) -> Result<Box<dyn Crane>, anyhow::Error> {
+    match s {
+        "9000" => Ok(Box::new(SinglePickUp)),
+        "9001" => Ok(Box::new(MultiPickUp)),
+        _ => Ok(Box::new(s.parse::<ConfigurableCrane>()?)),
+    }
+}
+
+/// Executes instruction number `step` (1-based) on stacks using given crane,
+/// returning its cost.
+pub fn execute_step(
+    crane: &dyn Crane,
+    stacks: &mut [Vec<char>],
+    step: usize,
+    instruction: &Instruction,
+) -> Result<u64, anyhow::Error> {
+    // 0-th stack is a dummy one, which instructions never refer to
+    for stack in [instruction.from, instruction.to] {
+        if stack == 0 || stack >= stacks.len() {
+            bail!("instruction {} refers to missing stack {}", step, stack);
+        }
+    }
+
+    crane
+        .execute(stacks, instruction)
+        .with_context(|| format!("executing instruction {}", step))
+}
+
+/// Executes instructions on stacks using given crane, returning rearranged
+/// stacks and total cost of all instructions.
+pub fn run_instructions(
+    crane: &dyn Crane,
+    stacks: &[Vec<char>],
+    instructions: &[Instruction],
+) -> Result<(Vec<Vec<char>>, u64), anyhow::Error> {
+    let mut stacks = stacks.to_vec();
+    let mut cost = 0;
+
+    for (step, instruction) in (1..).zip(instructions) {
+        cost += execute_step(crane, &mut stacks, step, instruction)?;
+    }
+
+    Ok((stacks, cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stacks() -> Vec<Vec<char>> {
+        vec![vec![], vec!['A', 'B', 'C', 'D', 'E'], vec![]]
+    }
+
+    fn move_all() -> Vec<Instruction> {
+        vec![Instruction {
+            num: 5,
+            from: 1,
+            to: 2,
+        }]
+    }
+
+    fn run(crane: &str) -> (Vec<char>, u64) {
+        let crane = parse_crane(crane).unwrap();
+        let (mut stacks, cost) = run_instructions(crane.as_ref(), &stacks(), &move_all()).unwrap();
+
+        (stacks.remove(2), cost)
+    }
+
+    #[test]
+    fn test_fixed_cranes() {
+        assert_eq!(run("9000"), ("EDCBA".chars().collect(), 5));
+        assert_eq!(run("9001"), ("ABCDE".chars().collect(), 1));
+        assert_eq!(run(""), run("9001"));
+    }
+
+    #[test]
+    fn test_capacity() {
+        // Lifts of DE, BC and A
+        assert_eq!(run("capacity=2"), ("DEBCA".chars().collect(), 3));
+        assert_eq!(run("capacity=1"), run("9000"));
+        assert_eq!(
+            run("capacity=2,lift-cost=10,crate-cost=1"),
+            ("DEBCA".chars().collect(), 35)
+        );
+    }
+
+    #[test]
+    fn test_flip() {
+        assert_eq!(run("flip=reverse"), ("EDCBA".chars().collect(), 1));
+        assert_eq!(run("flip=top:2"), ("ABCED".chars().collect(), 1));
+        assert_eq!(run("capacity=3,flip=top:2"), ("CEDBA".chars().collect(), 2));
+    }
+
+    #[test]
+    fn test_invalid_moves() {
+        let crane = parse_crane("capacity=2").unwrap();
+        let instructions = vec![Instruction {
+            num: 6,
+            from: 1,
+            to: 2,
+        }];
+        assert!(run_instructions(crane.as_ref(), &stacks(), &instructions).is_err());
+
+        let instructions = vec![Instruction {
+            num: 1,
+            from: 1,
+            to: 3,
+        }];
+        assert!(run_instructions(crane.as_ref(), &stacks(), &instructions).is_err());
+
+        assert!(parse_crane("capacity=0").is_err());
+        assert!(parse_crane("flip=sideways").is_err());
+        assert!(parse_crane("speed=9").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Rendering stacks back to the crate diagram of puzzle input, and replaying
+//! instructions one at a time.
+
+use anyhow::bail;
+
+use crate::{
+    crane::{execute_step, Crane},
+    Instruction,
+};
+
+/// Renders stacks the way they're drawn in puzzle input, with crates as `[X]`
+/// and stack numbers below them. Dummy 0-th stack is left out.
+pub fn render_stacks(stacks: &[Vec<char>]) -> String {
+    let stacks = stacks.get(1..).unwrap_or_default();
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut diagram = String::new();
+    for level in (0..height).rev() {
+        let row: Vec<String> = stacks
+            .iter()
+            .map(|stack| match stack.get(level) {
+                Some(c) => format!("[{}]", c),
+                None => "   ".to_string(),
+            })
+            .collect();
+
+        diagram.push_str(&row.join(" "));
+        diagram.push('\n');
+    }
+
+    let labels: Vec<String> = (1..=stacks.len())
+        .map(|number| format!("{:^3}", number))
+        .collect();
+    diagram.push_str(&labels.join(" "));
+    diagram.push('\n');
+
+    diagram
+}
+
+/// Executes instructions one at a time, keeping stacks after every step.
+pub struct Replay<'a> {
+    crane: &'a dyn Crane,
+    initial: &'a [Vec<char>],
+    instructions: &'a [Instruction],
+    stacks: Vec<Vec<char>>,
+    step: usize,
+    cost: u64,
+}
+
+impl<'a> Replay<'a> {
+    pub fn new(
+        crane: &'a dyn Crane,
+        initial: &'a [Vec<char>],
+        instructions: &'a [Instruction],
+    ) -> Self {
+        Self {
+            crane,
+            initial,
+            instructions,
+            stacks: initial.to_vec(),
+            step: 0,
+            cost: 0,
+        }
+    }
+
+    pub fn stacks(&self) -> &[Vec<char>] {
+        &self.stacks
+    }
+
+    /// Number of instructions executed so far.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Total cost of instructions executed so far.
+    pub fn cost(&self) -> u64 {
+        self.cost
+    }
+
+    /// Executes the next instruction, returning it, or `None` when all of
+    /// them have been executed.
+    pub fn advance(&mut self) -> Result<Option<&'a Instruction>, anyhow::Error> {
+        let Some(instruction) = self.instructions.get(self.step) else {
+            return Ok(None);
+        };
+
+        self.cost += execute_step(self.crane, &mut self.stacks, self.step + 1, instruction)?;
+        self.step += 1;
+
+        Ok(Some(instruction))
+    }
+
+    /// Moves to stacks after `step` instructions, going back to the initial
+    /// stacks first when `step` was already passed.
+    pub fn jump_to(&mut self, step: usize) -> Result<(), anyhow::Error> {
+        if step > self.instructions.len() {
+            bail!(
+                "can't jump to instruction {}, there are only {} of them",
+                step,
+                self.instructions.len()
+            );
+        }
+
+        if step < self.step {
+            self.stacks = self.initial.to_vec();
+            self.step = 0;
+            self.cost = 0;
+        }
+
+        while self.step < step {
+            self.advance()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crane::SinglePickUp, Problem};
+
+    const DIAGRAM_INPUT: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn test_render_stacks() {
+        let Problem { stacks, .. } = DIAGRAM_INPUT.parse().unwrap();
+        let (diagram, _) = DIAGRAM_INPUT.split_once("\n\n").unwrap();
+
+        assert_eq!(render_stacks(&stacks), format!("{}\n", diagram));
+        assert_eq!(render_stacks(&[vec![], vec![], vec![]]), " 1   2 \n");
+    }
+
+    #[test]
+    fn test_render_stacks_round_trip() {
+        let stacks = vec![vec![], vec!['A'], vec![], vec!['B', 'C', 'D'], vec![]];
+        let input = format!("{}\nmove 1 from 1 to 2", render_stacks(&stacks));
+        let Problem {
+            stacks: parsed_stacks,
+            ..
+        } = input.parse().unwrap();
+
+        assert_eq!(parsed_stacks, stacks);
+    }
+
+    #[test]
+    fn test_replay() {
+        let Problem {
+            stacks,
+            instructions,
+        } = DIAGRAM_INPUT.parse().unwrap();
+        let mut replay = Replay::new(&SinglePickUp, &stacks, &instructions);
+
+        assert_eq!(replay.advance().unwrap(), Some(&instructions[0]));
+        assert_eq!(
+            render_stacks(replay.stacks()),
+            "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n"
+        );
+
+        replay.jump_to(4).unwrap();
+        assert_eq!(replay.cost(), 7);
+        assert_eq!(replay.advance().unwrap(), None);
+
+        replay.jump_to(1).unwrap();
+        assert_eq!(replay.step(), 1);
+        assert_eq!(replay.cost(), 1);
+        assert!(replay.jump_to(5).is_err());
+    }
+}
@@ -1,15 +1,43 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
-
-#[derive(Debug, PartialEq)]
+#[cfg(not(feature = "serde"))]
+use common::read_problem;
+#[cfg(feature = "serde")]
+use common::serialization::read_problem;
+use common::{
+    get_arg, get_option, has_flag,
+    profiling::profile,
+    progress::{cancel_on_ctrl_c, get_budget, Progress},
+    read_file_to_string,
+};
+use crane::{parse_crane, run_instructions, MultiPickUp, SinglePickUp};
+use diagram::{render_stacks, Replay};
+use validation::validate;
+
+mod crane;
+mod diagram;
+mod planner;
+mod validation;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Instruction {
     num: usize,
     from: usize,
     to: usize,
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} from {} to {}", self.num, self.from, self.to)
+    }
+}
+
 impl FromStr for Instruction {
     type Err = anyhow::Error;
 
@@ -28,11 +56,51 @@ impl FromStr for Instruction {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Problem {
     stacks: Vec<Vec<char>>,
     instructions: Vec<Instruction>,
 }
 
+/// Parses stacks from the crate diagram, with labels row at the bottom.
+fn parse_stacks(stacks_raw: &str) -> Result<Vec<Vec<char>>, anyhow::Error> {
+    // Process stacks from the bottom-up
+    let mut stacks_iter = stacks_raw.lines().rev();
+
+    // Use first (bottom-most) stacks description line (labels) to determine
+    // their count
+    let stacks_count = stacks_iter
+        .next()
+        .ok_or_else(|| anyhow!("empty stacks description"))?
+        .split_whitespace()
+        .count();
+
+    // Allocate `stacks_count + 1` vectors to accommodate `stacks_count`
+    // 1-indexed stacks and a dummy 0-th stack, to not bother with
+    // re-indexing stacks. 0-th stack will simply never be touched when
+    // executing instructions, and is an issue only when reading tops of
+    // stacks to retrieve solution - a nuisance we can live with
+    let mut stacks = vec![vec![]; stacks_count + 1];
+
+    // Consider rest of stacks description input in (at most) 4-character
+    // long chunks. We will then encounter three kinds of input: (1) `[X] `
+    // - an item on stack, (2) `    ` - no item, (3) `[X]` - an item on
+    // stack in last column of input (note no space at the end), same as (1)
+    // for our purposes.
+    for stack_line in stacks_iter {
+        let chars = stack_line.chars().collect::<Vec<_>>();
+        for (stack_number, chunk) in chars.chunks(4).enumerate() {
+            if let Some(&c) = chunk.get(1) {
+                if c != ' ' {
+                    stacks[stack_number + 1].push(c);
+                }
+            }
+        }
+    }
+
+    Ok(stacks)
+}
+
 impl FromStr for Problem {
     type Err = anyhow::Error;
 
@@ -41,39 +109,7 @@ impl FromStr for Problem {
             .split_once("\n\n")
             .ok_or_else(|| anyhow!("expected input separated by single empty line"))?;
 
-        // Process stacks from the bottom-up
-        let mut stacks_iter = stacks_raw.lines().rev();
-
-        // Use first (bottom-most) stacks description line (labels) to determine
-        // their count
-        let stacks_count = stacks_iter
-            .next()
-            .ok_or_else(|| anyhow!("empty stacks description"))?
-            .split_whitespace()
-            .count();
-
-        // Allocate `stacks_count + 1` vectors to accommodate `stacks_count`
-        // 1-indexed stacks and a dummy 0-th stack, to not bother with
-        // re-indexing stacks. 0-th stack will simply never be touched when
-        // executing instructions, and is an issue only when reading tops of
-        // stacks to retrieve solution - a nuisance we can live with
-        let mut stacks = vec![vec![]; stacks_count + 1];
-
-        // Consider rest of stacks description input in (at most) 4-character
-        // long chunks. We will then encounter three kinds of input: (1) `[X] `
-        // - an item on stack, (2) `    ` - no item, (3) `[X]` - an item on
-        // stack in last column of input (note no space at the end), same as (1)
-        // for our purposes.
-        for stack_line in stacks_iter {
-            let chars = stack_line.chars().collect::<Vec<_>>();
-            for (stack_number, chunk) in chars.chunks(4).enumerate() {
-                if let Some(&c) = chunk.get(1) {
-                    if c != ' ' {
-                        stacks[stack_number + 1].push(c);
-                    }
-                }
-            }
-        }
+        let stacks = parse_stacks(stacks_raw)?;
 
         let instructions = instructions_raw
             .lines()
@@ -90,54 +126,7 @@ impl FromStr for Problem {
     }
 }
 
-/// Executes instructions on stacks using "single-item pick up" interpretation
-fn run_instructions_with_single_pick_up(
-    stacks: &Vec<Vec<char>>,
-    instructions: &Vec<Instruction>,
-) -> Result<Vec<Vec<char>>, anyhow::Error> {
-    let mut stacks = stacks.clone();
-
-    for instruction in instructions {
-        let Instruction { num, from, to } = *instruction;
-
-        // Move items one by one. Once could re-use loop-less solution from
-        // [`run_instructions_with_multi_pick_up`], by just reversing the order
-        // of items returned by [`std::vec::Vec::split_off`], but this way the
-        // intention is much clearer
-        for _ in 0..num {
-            let v = stacks[from]
-                .pop()
-                .ok_or_else(|| anyhow!("no items left on stack {}", from))?;
-            stacks[to].push(v);
-        }
-    }
-
-    Ok(stacks)
-}
-
-/// Executes instructions on stacks using "multi-item pick up" interpretation
-fn run_instructions_with_multi_pick_up(
-    stacks: &Vec<Vec<char>>,
-    instructions: &Vec<Instruction>,
-) -> Result<Vec<Vec<char>>, anyhow::Error> {
-    let mut stacks = stacks.clone();
-
-    for instruction in instructions {
-        let Instruction { num, from, to } = *instruction;
-
-        // move `num` items from the end of stack at once
-        let idx_to_pick_up_from = stacks[from]
-            .len()
-            .checked_sub(num)
-            .ok_or_else(|| anyhow!("stack {} has less than {} items left on it", from, num))?;
-        let mut v = stacks[from].split_off(idx_to_pick_up_from);
-        stacks[to].append(&mut v);
-    }
-
-    Ok(stacks)
-}
-
-fn read_tops_of_stacks(stacks: &Vec<Vec<char>>) -> String {
+fn read_tops_of_stacks(stacks: &[Vec<char>]) -> String {
     stacks
         .iter()
         .map(|stack| stack.last().unwrap_or(&' '))
@@ -146,27 +135,104 @@ fn read_tops_of_stacks(stacks: &Vec<Vec<char>>) -> String {
 
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
-    let input_string = read_file_to_string(&input_file_path)?;
     let Problem {
         stacks,
         instructions,
-    } = input_string.parse()?;
+    } = read_problem(&input_file_path)?;
+
+    if let Some(target_path) = get_option("--plan") {
+        let target_raw = read_file_to_string(&target_path)?;
+        let (target_raw, _) = target_raw.split_once("\n\n").unwrap_or((&target_raw, ""));
+        let target = parse_stacks(target_raw).context("couldn't parse target stacks")?;
+        let crane = parse_crane(&get_option("--crane").unwrap_or("9000".to_string()))
+            .context("couldn't parse --crane")?;
+
+        cancel_on_ctrl_c()?;
+        let progress = Progress::hidden(0).with_budget(get_budget()?);
+        let outcome = profile("plan", || {
+            planner::plan(crane.as_ref(), &stacks, &target, &progress)
+        })?;
+
+        match (outcome.is_partial(), outcome.into_inner()) {
+            (_, Some(plan)) => {
+                let (_, cost) = run_instructions(crane.as_ref(), &stacks, &plan)?;
+                println!("Plan of {} instructions (cost {}):", plan.len(), cost);
+                for instruction in plan {
+                    println!("{}", instruction);
+                }
+            }
+            (false, None) => println!("Crane can't rearrange stacks into target"),
+            (true, None) => println!("No plan found within time budget"),
+        }
+
+        return Ok(());
+    }
+
+    validate(&stacks, &instructions)?;
+    if has_flag("--validate") {
+        println!("All {} instructions are valid", instructions.len());
+
+        return Ok(());
+    }
+
+    let replay_step = match get_option("--step") {
+        Some(step) => Some(step.parse().context("couldn't parse --step")?),
+        None => None,
+    };
+    if has_flag("--replay") || replay_step.is_some() {
+        let crane = parse_crane(&get_option("--crane").unwrap_or("9000".to_string()))
+            .context("couldn't parse --crane")?;
+        let mut replay = Replay::new(crane.as_ref(), &stacks, &instructions);
+
+        replay.jump_to(replay_step.unwrap_or(0))?;
+        println!(
+            "After {} instructions (cost {}):",
+            replay.step(),
+            replay.cost()
+        );
+        print!("{}", render_stacks(replay.stacks()));
+
+        if has_flag("--replay") {
+            while let Some(instruction) = replay.advance()? {
+                println!(
+                    "\n{}. {} (cost {}):",
+                    replay.step(),
+                    instruction,
+                    replay.cost()
+                );
+                print!("{}", render_stacks(replay.stacks()));
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(crane) = get_option("--crane") {
+        let crane = parse_crane(&crane).context("couldn't parse --crane")?;
+        let (stacks, cost) = profile("crane", || {
+            run_instructions(crane.as_ref(), &stacks, &instructions)
+        })?;
+
+        println!("Tops of stacks: {}", read_tops_of_stacks(&stacks).trim());
+        println!("Total cost: {}", cost);
+
+        return Ok(());
+    }
+
+    let (stacks_pt1, _) = profile("part 1", || {
+        run_instructions(&SinglePickUp, &stacks, &instructions)
+    })?;
+    let (stacks_pt2, _) = profile("part 2", || {
+        run_instructions(&MultiPickUp, &stacks, &instructions)
+    })?;
 
     println!(
         "Part 1 solution: {}",
-        read_tops_of_stacks(&run_instructions_with_single_pick_up(
-            &stacks,
-            &instructions
-        )?)
-        .trim()
+        read_tops_of_stacks(&stacks_pt1).trim()
     );
     println!(
         "Part 2 solution: {}",
-        read_tops_of_stacks(&run_instructions_with_multi_pick_up(
-            &stacks,
-            &instructions
-        )?)
-        .trim()
+        read_tops_of_stacks(&stacks_pt2).trim()
     );
 
     Ok(())
@@ -231,12 +297,13 @@ move 1 from 1 to 2";
             instructions,
         } = TEST_INPUT.parse().unwrap();
 
-        let stacks = run_instructions_with_single_pick_up(&stacks, &instructions).unwrap();
+        let (stacks, cost) = run_instructions(&SinglePickUp, &stacks, &instructions).unwrap();
 
         assert_eq!(
             stacks,
             vec![vec![], vec!['C'], vec!['M'], vec!['P', 'D', 'N', 'Z']]
         );
+        assert_eq!(cost, 7);
     }
 
     #[test]
@@ -246,12 +313,13 @@ move 1 from 1 to 2";
             instructions,
         } = TEST_INPUT.parse().unwrap();
 
-        let stacks = run_instructions_with_multi_pick_up(&stacks, &instructions).unwrap();
+        let (stacks, cost) = run_instructions(&MultiPickUp, &stacks, &instructions).unwrap();
 
         assert_eq!(
             stacks,
             vec![vec![], vec!['M'], vec!['C'], vec!['P', 'Z', 'N', 'D']]
         );
+        assert_eq!(cost, 4);
     }
 
     #[test]
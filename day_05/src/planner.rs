@@ -0,0 +1,168 @@
+//! Planning the shortest rearrangement of stacks into a target arrangement.
+
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::bail;
+use common::progress::{Outcome, Progress};
+
+use crate::{crane::Crane, Instruction};
+
+/// Arrangement of stacks, with index of the arrangement it was reached from and
+/// instruction which did that
+type Visited = (Vec<Vec<char>>, Option<(usize, Instruction)>);
+
+/// Finds the shortest sequence of instructions which rearranges `stacks` into
+/// `target` with given crane, with breadth-first search over arrangements.
+/// Returns `None` when the crane can't produce target arrangement at all.
+///
+/// Number of arrangements grows exponentially with number of crates, so
+/// [`Outcome::Partial`] is returned when `progress` stopped the search early,
+/// in which case it's unknown whether a plan exists.
+pub fn plan(
+    crane: &dyn Crane,
+    stacks: &[Vec<char>],
+    target: &[Vec<char>],
+    progress: &Progress,
+) -> Result<Outcome<Option<Vec<Instruction>>>, anyhow::Error> {
+    if stacks.len() != target.len() {
+        bail!(
+            "target has {} stacks, but there are {}",
+            target.len().saturating_sub(1),
+            stacks.len().saturating_sub(1)
+        );
+    }
+
+    let mut crates = stacks.concat();
+    let mut target_crates = target.concat();
+    crates.sort_unstable();
+    target_crates.sort_unstable();
+    if crates != target_crates {
+        bail!("target has different crates than initial stacks");
+    }
+
+    let mut arrangements: Vec<Visited> = vec![(stacks.to_vec(), None)];
+    let mut seen: HashMap<Vec<Vec<char>>, usize> = HashMap::from([(stacks.to_vec(), 0)]);
+    let mut queue = VecDeque::from([0]);
+
+    let found = loop {
+        let Some(current) = queue.pop_front() else {
+            break None;
+        };
+        if arrangements[current].0 == target {
+            break Some(current);
+        }
+        if progress.should_stop() {
+            break None;
+        }
+
+        for from in 1..stacks.len() {
+            for to in (1..stacks.len()).filter(|&to| to != from) {
+                for num in 1..=arrangements[current].0[from].len() {
+                    let instruction = Instruction { num, from, to };
+                    let mut next = arrangements[current].0.clone();
+                    crane.execute(&mut next, &instruction)?;
+
+                    if !seen.contains_key(&next) {
+                        seen.insert(next.clone(), arrangements.len());
+                        queue.push_back(arrangements.len());
+                        arrangements.push((next, Some((current, instruction))));
+                    }
+                }
+            }
+        }
+    };
+
+    let instructions = found.map(|mut current| {
+        let mut instructions = vec![];
+        while let Some((previous, instruction)) = arrangements[current].1.take() {
+            instructions.push(instruction);
+            current = previous;
+        }
+        instructions.reverse();
+
+        instructions
+    });
+
+    Ok(progress.finish(instructions))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::crane::{parse_crane, run_instructions, MultiPickUp, SinglePickUp};
+
+    fn stacks(s: &str) -> Vec<Vec<char>> {
+        std::iter::once(vec![])
+            .chain(s.split('|').map(|stack| stack.chars().collect()))
+            .collect()
+    }
+
+    fn find_plan(crane: &dyn Crane, from: &str, to: &str) -> Option<Vec<Instruction>> {
+        let (from, to) = (stacks(from), stacks(to));
+        let plan = plan(crane, &from, &to, &Progress::hidden(0))
+            .unwrap()
+            .into_inner();
+
+        if let Some(instructions) = &plan {
+            let (stacks, _) = run_instructions(crane, &from, instructions).unwrap();
+            assert_eq!(stacks, to);
+        }
+
+        plan
+    }
+
+    #[test]
+    fn test_plan() {
+        // Single pick up crane reverses the whole stack with one instruction
+        let plan = find_plan(&SinglePickUp, "ABC||", "|CBA|").unwrap();
+        assert_eq!(
+            plan,
+            vec![Instruction {
+                num: 3,
+                from: 1,
+                to: 2
+            }]
+        );
+
+        // Multi pick up crane needs to go through the third stack
+        assert_eq!(find_plan(&MultiPickUp, "ABC||", "|CBA|").unwrap().len(), 3);
+
+        assert_eq!(find_plan(&MultiPickUp, "AB|C", "AB|C"), Some(vec![]));
+    }
+
+    #[test]
+    fn test_plan_with_custom_crane() {
+        let crane = parse_crane("capacity=2").unwrap();
+
+        // Lifts of BC and A leave crates as BCA
+        let plan = find_plan(crane.as_ref(), "ABC|", "|BCA").unwrap();
+        assert_eq!(
+            plan,
+            vec![Instruction {
+                num: 3,
+                from: 1,
+                to: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_plan_impossible() {
+        // With a single stack, crates can't move at all
+        assert_eq!(find_plan(&MultiPickUp, "AB", "BA"), None);
+
+        let progress = Progress::hidden(0);
+        assert!(plan(&MultiPickUp, &stacks("AB|"), &stacks("AB|C"), &progress).is_err());
+        assert!(plan(&MultiPickUp, &stacks("AB|"), &stacks("AB||"), &progress).is_err());
+    }
+
+    #[test]
+    fn test_plan_out_of_budget() {
+        let progress = Progress::hidden(0).with_budget(Some(Duration::ZERO));
+        let plan = plan(&MultiPickUp, &stacks("ABC||"), &stacks("|CBA|"), &progress).unwrap();
+
+        assert!(plan.is_partial());
+    }
+}
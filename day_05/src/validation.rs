@@ -0,0 +1,121 @@
+//! Checking instructions against initial stacks without executing them.
+
+use std::fmt::Display;
+
+use crate::Instruction;
+
+/// Reason an instruction can't be executed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Invalid {
+    /// Instruction refers to a stack which doesn't exist
+    MissingStack(usize),
+    /// Instruction moves more crates than there are on a stack at that point
+    NotEnoughCrates { stack: usize, crates: usize },
+}
+
+/// First instruction which can't be executed, with its 1-based number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidStep {
+    pub step: usize,
+    pub instruction: Instruction,
+    pub reason: Invalid,
+}
+
+impl Display for InvalidStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "instruction {} ({}) ", self.step, self.instruction)?;
+
+        match self.reason {
+            Invalid::MissingStack(stack) => write!(f, "refers to missing stack {}", stack),
+            Invalid::NotEnoughCrates { stack, crates } => write!(
+                f,
+                "moves {} crates, but stack {} has only {} at that point",
+                self.instruction.num, stack, crates
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidStep {}
+
+/// Checks that every instruction can be executed, given initial stacks. Every
+/// crane moves the same number of crates, so it's enough to track heights of
+/// stacks, regardless of which crane is going to execute instructions.
+pub fn validate(stacks: &[Vec<char>], instructions: &[Instruction]) -> Result<(), InvalidStep> {
+    let mut heights: Vec<usize> = stacks.iter().map(Vec::len).collect();
+
+    for (step, instruction) in (1..).zip(instructions) {
+        let Instruction { num, from, to } = *instruction;
+        let invalid = |reason| InvalidStep {
+            step,
+            instruction: instruction.clone(),
+            reason,
+        };
+
+        // 0-th stack is a dummy one, which instructions never refer to
+        for stack in [from, to] {
+            if stack == 0 || stack >= heights.len() {
+                return Err(invalid(Invalid::MissingStack(stack)));
+            }
+        }
+
+        if heights[from] < num {
+            return Err(invalid(Invalid::NotEnoughCrates {
+                stack: from,
+                crates: heights[from],
+            }));
+        }
+
+        heights[from] -= num;
+        heights[to] += num;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instruction(num: usize, from: usize, to: usize) -> Instruction {
+        Instruction { num, from, to }
+    }
+
+    #[test]
+    fn test_validate() {
+        let stacks = vec![vec![], vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']];
+        let mut instructions = vec![
+            instruction(1, 2, 1),
+            instruction(3, 1, 3),
+            instruction(2, 2, 1),
+            instruction(1, 1, 2),
+        ];
+        assert_eq!(validate(&stacks, &instructions), Ok(()));
+
+        instructions.push(instruction(2, 2, 3));
+        let invalid = validate(&stacks, &instructions).unwrap_err();
+        assert_eq!(invalid.step, 5);
+        assert_eq!(
+            invalid.reason,
+            Invalid::NotEnoughCrates {
+                stack: 2,
+                crates: 1
+            }
+        );
+        assert_eq!(
+            invalid.to_string(),
+            "instruction 5 (move 2 from 2 to 3) moves 2 crates, but stack 2 has only 1 at that point"
+        );
+    }
+
+    #[test]
+    fn test_validate_missing_stack() {
+        let stacks = vec![vec![], vec!['A']];
+
+        for instruction in [instruction(1, 1, 2), instruction(1, 0, 1)] {
+            let invalid = validate(&stacks, &[instruction]).unwrap_err();
+            assert_eq!(invalid.step, 1);
+            assert!(matches!(invalid.reason, Invalid::MissingStack(_)));
+        }
+    }
+}
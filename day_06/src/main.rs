@@ -1,54 +1,101 @@
+use std::{fs::File, num::NonZeroUsize};
+
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, get_option, has_flag, profiling::profile, read_file_to_string};
+use marker::{find_first_marker, find_markers};
+use protocol::{Decoder, Frame};
 
-fn find_start_of_packet(packet: &str) -> Option<usize> {
-    let idx = packet
-        .chars()
-        .collect::<Vec<_>>()
-        .windows(4)
-        .position(|window| {
-            let [a, b, c, d]: [char; 4] = window.try_into().unwrap();
-
-            a != b && a != c && a != d && b != c && b != d && c != d
-        });
+mod marker;
+mod protocol;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
-    idx.map(|i| i + 4)
+fn find_start_of_packet(packet: &str) -> Option<usize> {
+    find_first_marker(packet, NonZeroUsize::new(4).unwrap())
 }
 
 fn find_start_of_message(packet: &str) -> Option<usize> {
-    let idx = packet
-        .chars()
-        .collect::<Vec<_>>()
-        .windows(14)
-        .position(|window| {
-            // you might see a double loop here, but I see constant amount of
-            // work per input array item 🤡
-            for (i, a) in window.iter().enumerate() {
-                for b in &window[(i + 1)..] {
-                    if a == b {
-                        return false;
-                    }
-                }
-            }
-
-            true
-        });
+    find_first_marker(packet, NonZeroUsize::new(14).unwrap())
+}
 
-    idx.map(|i| i + 14)
+/// Parses size of marker window, which has to be positive, as an empty window
+/// would be a marker after every byte.
+fn parse_window(s: &str) -> Result<NonZeroUsize, anyhow::Error> {
+    NonZeroUsize::new(s.parse().context("couldn't parse --window")?)
+        .ok_or_else(|| anyhow!("--window has to be at least 1"))
 }
 
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
+
+    if let Some(window) = get_option("--window") {
+        let window = parse_window(&window)?;
+        let file = File::open(&input_file_path)
+            .with_context(|| format!("couldn't open {}", input_file_path))?;
+        let mut markers = find_markers(file, window);
+
+        if has_flag("--all") {
+            let markers = profile("markers", || markers.collect::<Result<Vec<_>, _>>())?;
+            println!("Markers of {} distinct bytes: {}", window, markers.len());
+            for position in markers {
+                println!("{}", position);
+            }
+        } else {
+            let position = profile("marker", || markers.next())
+                .ok_or_else(|| anyhow!("couldn't find marker of {} distinct bytes", window))??;
+            println!("First marker of {} distinct bytes: {}", window, position);
+        }
+
+        return Ok(());
+    }
+
     let input_string = read_file_to_string(&input_file_path)?;
 
+    if has_flag("--frames") {
+        let max_payload = match get_option("--max-payload") {
+            Some(max) => max.parse().context("couldn't parse --max-payload")?,
+            None => usize::MAX,
+        };
+        let frames: Vec<Frame> = profile("frames", || {
+            Decoder::new(input_string.trim_end().as_bytes(), max_payload).collect()
+        });
+
+        for frame in &frames {
+            let (kind, offset, bytes) = match frame {
+                Frame::Packet { offset, payload } => ("packet", offset, payload),
+                Frame::Message { offset, payload } => ("message", offset, payload),
+                Frame::Corrupt { offset, bytes } => ("corrupt", offset, bytes),
+            };
+            println!(
+                "{:>8} {:<8} {:>5} {}",
+                offset,
+                kind,
+                bytes.len(),
+                String::from_utf8_lossy(bytes)
+            );
+        }
+
+        let count = |kind: fn(&Frame) -> bool| frames.iter().filter(|f| kind(f)).count();
+        println!(
+            "Packets: {}, messages: {}, corrupt regions: {}",
+            count(|f| matches!(f, Frame::Packet { .. })),
+            count(|f| matches!(f, Frame::Message { .. })),
+            count(|f| matches!(f, Frame::Corrupt { .. })),
+        );
+
+        return Ok(());
+    }
+
     println!(
         "Part 1 solution: {}",
-        find_start_of_packet(&input_string)
+        profile("part 1", || find_start_of_packet(&input_string))
             .ok_or_else(|| anyhow!("couldn't find start of packet"))?
     );
     println!(
         "Part 2 solution: {}",
-        find_start_of_message(&input_string)
+        profile("part 2", || find_start_of_message(&input_string))
             .ok_or_else(|| anyhow!("couldn't find start of message"))?
     );
 
@@ -106,4 +153,14 @@ mod tests {
             Some(26)
         );
     }
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("14").unwrap().get(), 14);
+        assert_eq!(
+            parse_window("0").unwrap_err().to_string(),
+            "--window has to be at least 1"
+        );
+        assert!(parse_window("-1").is_err());
+    }
 }
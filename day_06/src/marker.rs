@@ -0,0 +1,205 @@
+//! Detecting markers, i.e. runs of distinct bytes, in datastreams of any
+//! length.
+
+use std::{
+    collections::VecDeque,
+    io::{BufReader, Bytes, Read},
+    num::NonZeroUsize,
+};
+
+/// Sliding window over the most recent bytes of a datastream, tracking how
+/// many times every byte value occurs within it.
+#[derive(Clone, Debug)]
+pub struct MarkerDetector {
+    window: usize,
+    recent: VecDeque<u8>,
+    counts: [usize; 256],
+    /// Number of byte values occurring more than once within the window
+    repeated: usize,
+    position: usize,
+}
+
+impl MarkerDetector {
+    /// Creates detector of markers of `window` distinct bytes. Window can't
+    /// be empty, as that would be a marker after every byte.
+    pub fn new(window: NonZeroUsize) -> Self {
+        let window = window.get();
+
+        Self {
+            window,
+            recent: VecDeque::with_capacity(window + 1),
+            counts: [0; 256],
+            repeated: 0,
+            position: 0,
+        }
+    }
+
+    /// Feeds the next byte of datastream, returning whether it completes a
+    /// marker. Takes constant time, regardless of window size.
+    pub fn push(&mut self, byte: u8) -> bool {
+        self.position += 1;
+
+        self.recent.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.repeated += 1;
+        }
+
+        if self.recent.len() > self.window {
+            let oldest = self.recent.pop_front().unwrap() as usize;
+            self.counts[oldest] -= 1;
+            if self.counts[oldest] == 1 {
+                self.repeated -= 1;
+            }
+        }
+
+        self.recent.len() == self.window && self.repeated == 0
+    }
+
+    /// Number of bytes fed so far, which is where a marker ends right after
+    /// [`MarkerDetector::push`] detects it.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// Positions right after every marker in a datastream, including overlapping
+/// ones.
+pub struct Markers<R> {
+    bytes: Bytes<BufReader<R>>,
+    detector: MarkerDetector,
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = Result<usize, anyhow::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for byte in self.bytes.by_ref() {
+            match byte {
+                Ok(byte) if self.detector.push(byte) => return Some(Ok(self.detector.position())),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+
+        None
+    }
+}
+
+/// Finds markers of `window` distinct bytes in a datastream read from
+/// `reader`, holding only the last `window` bytes in memory.
+pub fn find_markers<R: Read>(reader: R, window: NonZeroUsize) -> Markers<R> {
+    Markers {
+        bytes: BufReader::new(reader).bytes(),
+        detector: MarkerDetector::new(window),
+    }
+}
+
+/// Finds position right after the first marker of `window` distinct bytes.
+pub fn find_first_marker(datastream: &str, window: NonZeroUsize) -> Option<usize> {
+    let mut detector = MarkerDetector::new(window);
+
+    datastream
+        .bytes()
+        .position(|byte| detector.push(byte))
+        .map(|i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    fn window(size: usize) -> NonZeroUsize {
+        NonZeroUsize::new(size).unwrap()
+    }
+
+    fn brute_force_markers(datastream: &str, window: usize) -> Vec<usize> {
+        let bytes = datastream.as_bytes();
+
+        (window..=bytes.len())
+            .filter(|&end| {
+                let window = &bytes[end - window..end];
+
+                (0..window.len()).all(|i| !window[i + 1..].contains(&window[i]))
+            })
+            .collect()
+    }
+
+    /// Reader handing out a single byte at a time, failing once it runs out
+    /// of bytes when `fail` is set.
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        fail: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.bytes.split_first() {
+                Some((&byte, rest)) if !buf.is_empty() => {
+                    buf[0] = byte;
+                    self.bytes = rest;
+                    Ok(1)
+                }
+                None if self.fail => Err(io::Error::other("connection lost")),
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_markers_matches_brute_force() {
+        let datastream = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsgzcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
+
+        for size in [1, 2, 4, 7, 14, 20] {
+            let markers: Vec<usize> = find_markers(datastream.as_bytes(), window(size))
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            assert_eq!(markers, brute_force_markers(datastream, size));
+            assert_eq!(
+                find_first_marker(datastream, window(size)),
+                markers.first().copied()
+            );
+        }
+    }
+
+    #[test]
+    fn test_window_size() {
+        // A single byte is always distinct, but there's no marker before it
+        assert_eq!(find_first_marker("aab", window(1)), Some(1));
+        assert_eq!(find_first_marker("", window(1)), None);
+        assert_eq!(find_first_marker("abc", window(4)), None);
+    }
+
+    #[test]
+    fn test_find_markers_streaming() {
+        let reader = Trickle {
+            bytes: b"mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            fail: false,
+        };
+        let markers: Vec<usize> = find_markers(reader, window(4))
+            .take(3)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(markers, vec![7, 8, 9]);
+
+        // Only the last few bytes of a long datastream are ever kept around
+        let endless = io::repeat(b'a').take(1 << 20).chain(&b"bcd"[..]);
+        let last = find_markers(endless, window(4)).last().unwrap().unwrap();
+        assert_eq!(last, (1 << 20) + 3);
+    }
+
+    #[test]
+    fn test_find_markers_read_error() {
+        let reader = Trickle {
+            bytes: b"abcd",
+            fail: true,
+        };
+        let mut markers = find_markers(reader, window(4));
+
+        assert_eq!(markers.next().unwrap().unwrap(), 4);
+        assert!(markers.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Decoding datastreams into packets and messages, which start with their
+//! markers.
+
+use std::num::NonZeroUsize;
+
+use crate::marker::MarkerDetector;
+
+pub const PACKET_MARKER_LEN: usize = 4;
+pub const MESSAGE_MARKER_LEN: usize = 14;
+
+/// Part of a datastream, with offset of its first byte.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Frame<'a> {
+    /// Start-of-packet marker followed by its payload
+    Packet { offset: usize, payload: &'a [u8] },
+    /// Start-of-message marker followed by its payload
+    Message { offset: usize, payload: &'a [u8] },
+    /// Bytes which don't belong to any frame, as they're either before the
+    /// first marker, or too far away from the last one
+    Corrupt { offset: usize, bytes: &'a [u8] },
+}
+
+/// Checks if all bytes are distinct.
+fn is_marker(bytes: &[u8]) -> bool {
+    let mut seen = [false; 256];
+
+    bytes
+        .iter()
+        .all(|&byte| !std::mem::replace(&mut seen[byte as usize], true))
+}
+
+/// Splits datastream into frames, each starting at the first marker after the
+/// previous one. Markers don't overlap, and when both kinds of markers start
+/// at the same byte, it's a message.
+pub struct Decoder<'a> {
+    data: &'a [u8],
+    max_payload: usize,
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Creates decoder of `data`, treating bytes more than `max_payload` bytes
+    /// away from the end of the last marker as corrupt.
+    pub fn new(data: &'a [u8], max_payload: usize) -> Self {
+        Self {
+            data,
+            max_payload,
+            position: 0,
+        }
+    }
+
+    /// Finds where the first marker at or after `from` starts, or returns end
+    /// of data if there's none.
+    fn find_marker_start(&self, from: usize) -> usize {
+        let mut detector = MarkerDetector::new(NonZeroUsize::new(PACKET_MARKER_LEN).unwrap());
+
+        self.data[from..]
+            .iter()
+            .position(|&byte| detector.push(byte))
+            .map_or(self.data.len(), |i| from + i + 1 - PACKET_MARKER_LEN)
+    }
+}
+
+impl<'a> Iterator for Decoder<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.position;
+        if offset == self.data.len() {
+            return None;
+        }
+
+        let marker_start = self.find_marker_start(offset);
+        if offset < marker_start {
+            self.position = marker_start;
+
+            return Some(Frame::Corrupt {
+                offset,
+                bytes: &self.data[offset..marker_start],
+            });
+        }
+
+        // Every message marker starts with a packet marker
+        let is_message = self
+            .data
+            .get(offset..offset + MESSAGE_MARKER_LEN)
+            .is_some_and(is_marker);
+        let marker_len = match is_message {
+            true => MESSAGE_MARKER_LEN,
+            false => PACKET_MARKER_LEN,
+        };
+
+        let payload_start = offset + marker_len;
+        let payload_end = self
+            .find_marker_start(payload_start)
+            .min(payload_start.saturating_add(self.max_payload));
+        let payload = &self.data[payload_start..payload_end];
+        self.position = payload_end;
+
+        Some(match is_message {
+            true => Frame::Message { offset, payload },
+            false => Frame::Packet { offset, payload },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATASTREAM: &[u8] = b"aaaabcdffffghijklmnopqrssss";
+
+    #[test]
+    fn test_decoder() {
+        let frames: Vec<Frame> = Decoder::new(DATASTREAM, usize::MAX).collect();
+
+        assert_eq!(
+            frames,
+            vec![
+                Frame::Corrupt {
+                    offset: 0,
+                    bytes: b"aaa"
+                },
+                Frame::Packet {
+                    offset: 3,
+                    payload: b"fff"
+                },
+                Frame::Message {
+                    offset: 10,
+                    payload: b"sss"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decoder_max_payload() {
+        let frames: Vec<Frame> = Decoder::new(DATASTREAM, 2).collect();
+
+        assert_eq!(
+            frames,
+            vec![
+                Frame::Corrupt {
+                    offset: 0,
+                    bytes: b"aaa"
+                },
+                Frame::Packet {
+                    offset: 3,
+                    payload: b"ff"
+                },
+                Frame::Corrupt {
+                    offset: 9,
+                    bytes: b"f"
+                },
+                Frame::Message {
+                    offset: 10,
+                    payload: b"ss"
+                },
+                Frame::Corrupt {
+                    offset: 26,
+                    bytes: b"s"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decoder_without_markers() {
+        assert_eq!(Decoder::new(b"", 10).next(), None);
+        assert_eq!(
+            Decoder::new(b"abab", 10).collect::<Vec<_>>(),
+            vec![Frame::Corrupt {
+                offset: 0,
+                bytes: b"abab"
+            }]
+        );
+    }
+}
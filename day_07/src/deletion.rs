@@ -0,0 +1,266 @@
+//! Planning deletion of multiple directories and files, which together free up
+//! enough space.
+
+use std::{collections::HashSet, str::FromStr};
+
+use anyhow::bail;
+use common::progress::{Outcome, Progress};
+
+use crate::fs::{Filesystem, NodeId, ROOT};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// Smallest possible total size, with branch and bound search
+    Exact,
+    /// Quick approximation, which may delete more than necessary
+    Greedy,
+}
+
+impl FromStr for Mode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(Self::Exact),
+            "greedy" => Ok(Self::Greedy),
+            _ => bail!("unknown mode: {} (expected 'exact' or 'greedy')", s),
+        }
+    }
+}
+
+/// Directories and files to delete, none of them within another one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deletion {
+    pub nodes: Vec<NodeId>,
+    /// Total size of deleted nodes
+    pub freed: u64,
+}
+
+impl Deletion {
+    fn new(mut nodes: Vec<NodeId>, sizes: &[u64]) -> Self {
+        nodes.sort_unstable();
+        let freed = nodes.iter().map(|&id| sizes[id]).sum();
+
+        Self { nodes, freed }
+    }
+}
+
+/// Marks node, all of its ancestors and everything within it, which can't be
+/// deleted once the node is.
+fn block(fs: &Filesystem, blocked: &mut [bool], id: NodeId) {
+    let mut ancestor = fs.node(id).parent;
+    while let Some(id) = ancestor {
+        blocked[id] = true;
+        ancestor = fs.node(id).parent;
+    }
+
+    let mut within = vec![id];
+    while let Some(id) = within.pop() {
+        blocked[id] = true;
+        within.extend(fs.children(id));
+    }
+}
+
+/// Deletes the largest nodes which don't free up more than needed, keeping
+/// track of the smallest node which would finish the job at every step.
+/// Returns `None` when even deleting everything isn't enough.
+pub fn plan_greedy(fs: &Filesystem, sizes: &[u64], to_free: u64) -> Option<Deletion> {
+    let mut blocked = vec![false; sizes.len()];
+    let mut chosen = vec![];
+    let mut remaining = to_free;
+    let mut best: Option<Deletion> = None;
+
+    while remaining > 0 {
+        let available = || fs.ids().filter(|&id| !blocked[id] && sizes[id] > 0);
+
+        if let Some(last) = available()
+            .filter(|&id| sizes[id] >= remaining)
+            .min_by_key(|&id| sizes[id])
+        {
+            let finished = Deletion::new([chosen.as_slice(), &[last]].concat(), sizes);
+            if best.as_ref().is_none_or(|best| finished.freed < best.freed) {
+                best = Some(finished);
+            }
+        }
+
+        let Some(next) = available()
+            .filter(|&id| sizes[id] < remaining)
+            .max_by_key(|&id| sizes[id])
+        else {
+            break;
+        };
+        block(fs, &mut blocked, next);
+        chosen.push(next);
+        remaining -= sizes[next];
+    }
+
+    match remaining {
+        0 => Some(Deletion::new(chosen, sizes)),
+        _ => best,
+    }
+}
+
+/// State of the exact search, over nodes in pre-order, with larger children
+/// visited first. Choosing a node skips everything within it.
+struct Search<'a> {
+    sizes: &'a [u64],
+    order: Vec<NodeId>,
+    /// Position in `order` right after everything within a node at given one
+    skip_to: Vec<usize>,
+    /// Total size of files from given position on, which bounds how much more
+    /// can be freed
+    files_left: Vec<u64>,
+    to_free: u64,
+    chosen: Vec<NodeId>,
+    best: Option<Deletion>,
+    /// Positions and freed space which are already known not to lead to
+    /// anything better than `best`
+    visited: HashSet<(usize, u64)>,
+    progress: &'a Progress,
+}
+
+impl Search<'_> {
+    fn run(&mut self, position: usize, freed: u64) {
+        if position == self.order.len()
+            || freed + self.files_left[position] < self.to_free
+            || !self.visited.insert((position, freed))
+            || self.progress.should_stop()
+        {
+            return;
+        }
+
+        let id = self.order[position];
+        let with_node = freed + self.sizes[id];
+        if with_node >= self.to_free {
+            if self.best.as_ref().is_none_or(|best| with_node < best.freed) {
+                let nodes = [self.chosen.as_slice(), &[id]].concat();
+                self.best = Some(Deletion::new(nodes, self.sizes));
+            }
+        } else if self.sizes[id] > 0 {
+            self.chosen.push(id);
+            self.run(self.skip_to[position], with_node);
+            self.chosen.pop();
+        }
+
+        // Nothing can beat freeing up exactly as much as needed
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|best| best.freed > self.to_free)
+        {
+            self.run(position + 1, freed);
+        }
+    }
+}
+
+/// Finds nodes of the smallest total size which free up at least `to_free`,
+/// starting from the greedy plan. Returns `None` when even deleting everything
+/// isn't enough.
+///
+/// Search is exponential in the worst case, so [`Outcome::Partial`] is
+/// returned when `progress` stopped it early, with the best plan found so far.
+pub fn plan_exact(
+    fs: &Filesystem,
+    sizes: &[u64],
+    to_free: u64,
+    progress: &Progress,
+) -> Outcome<Option<Deletion>> {
+    if to_free == 0 {
+        return progress.finish(Some(Deletion::new(vec![], sizes)));
+    }
+
+    let mut order = vec![];
+    let mut skip_to = vec![0; sizes.len()];
+    let mut stack = vec![(ROOT, false)];
+    while let Some((id, visited)) = stack.pop() {
+        if visited {
+            skip_to[id] = order.len();
+            continue;
+        }
+
+        order.push(id);
+        stack.push((id, true));
+        let mut children: Vec<NodeId> = fs.children(id).collect();
+        children.sort_by_key(|&child| sizes[child]);
+        stack.extend(children.into_iter().map(|child| (child, false)));
+    }
+    let skip_to = order.iter().map(|&id| skip_to[id]).collect();
+
+    let mut files_left = vec![0; order.len() + 1];
+    for position in (0..order.len()).rev() {
+        let id = order[position];
+        let file_size = if fs.is_dir(id) { 0 } else { sizes[id] };
+        files_left[position] = files_left[position + 1] + file_size;
+    }
+
+    let mut search = Search {
+        sizes,
+        order,
+        skip_to,
+        files_left,
+        to_free,
+        chosen: vec![],
+        best: plan_greedy(fs, sizes, to_free),
+        visited: HashSet::new(),
+        progress,
+    };
+    search.run(0, 0);
+
+    progress.finish(search.best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str = "\
+$ ls
+dir x
+dir y
+$ cd x
+$ ls
+6 a
+5 b
+$ cd ../y
+$ ls
+5 c
+dir z
+$ cd z
+$ ls
+3 d";
+
+    fn plan(to_free: u64, mode: Mode) -> Option<(Vec<String>, u64)> {
+        let fs: Filesystem = TRANSCRIPT.parse().unwrap();
+        let sizes = fs.sizes();
+        let deletion = match mode {
+            Mode::Exact => plan_exact(&fs, &sizes, to_free, &Progress::hidden(0)).into_inner(),
+            Mode::Greedy => plan_greedy(&fs, &sizes, to_free),
+        }?;
+
+        let mut paths: Vec<String> = deletion.nodes.iter().map(|&id| fs.path(id)).collect();
+        paths.sort();
+        Some((paths, deletion.freed))
+    }
+
+    #[test]
+    fn test_plan_exact() {
+        assert_eq!(
+            plan(10, Mode::Exact),
+            Some((vec!["/x/b".to_string(), "/y/c".to_string()], 10))
+        );
+        assert_eq!(plan(8, Mode::Exact), Some((vec!["/y".to_string()], 8)));
+        assert_eq!(plan(19, Mode::Exact), Some((vec!["/".to_string()], 19)));
+        assert_eq!(plan(20, Mode::Exact), None);
+        assert_eq!(plan(0, Mode::Exact), Some((vec![], 0)));
+    }
+
+    #[test]
+    fn test_plan_greedy() {
+        // Taking the largest fitting /y leaves 2 to free, which costs more
+        // than just /x, whereas 5 + 5 would do
+        assert_eq!(plan(10, Mode::Greedy), Some((vec!["/x".to_string()], 11)));
+        assert_eq!(plan(8, Mode::Greedy), Some((vec!["/y".to_string()], 8)));
+        assert_eq!(plan(20, Mode::Greedy), None);
+        assert_eq!(plan(0, Mode::Greedy), Some((vec![], 0)));
+    }
+}
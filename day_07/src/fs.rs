@@ -0,0 +1,416 @@
+//! Filesystem tree reconstructed from a terminal transcript of `cd` and `ls`
+//! commands.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context};
+
+/// Index of a node within [`Filesystem`].
+pub type NodeId = usize;
+
+pub const ROOT: NodeId = 0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeKind {
+    File {
+        size: u64,
+    },
+    Dir {
+        children: BTreeMap<String, NodeId>,
+        /// Whether contents of the directory are known from `ls`, rather than
+        /// it being only entered with `cd`
+        listed: bool,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+}
+
+/// Single line of `ls` output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Entry {
+    Dir,
+    File(u64),
+}
+
+impl FromStr for Entry {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dir" => Ok(Entry::Dir),
+            size => Ok(Entry::File(size.parse().with_context(|| {
+                format!("couldn't parse file size from '{}'", size)
+            })?)),
+        }
+    }
+}
+
+/// Directories and files, with parents always preceding their children.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filesystem {
+    nodes: Vec<Node>,
+}
+
+impl Filesystem {
+    /// All nodes, every parent before its children.
+    pub fn ids(&self) -> std::ops::Range<NodeId> {
+        0..self.nodes.len()
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, NodeKind::Dir { .. })
+    }
+
+    /// Children of a directory, ordered by name. Files have none.
+    pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let children = match &self.nodes[id].kind {
+            NodeKind::Dir { children, .. } => Some(children.values().copied()),
+            NodeKind::File { .. } => None,
+        };
+
+        children.into_iter().flatten()
+    }
+
+    /// Absolute path of a node, `/` for the root directory.
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = vec![];
+        let mut current = Some(id);
+        while let Some(id) = current.filter(|&id| id != ROOT) {
+            names.push(self.nodes[id].name.as_str());
+            current = self.nodes[id].parent;
+        }
+        names.reverse();
+
+        format!("/{}", names.join("/"))
+    }
+
+    /// Total size of every node, which for directories includes everything
+    /// within them.
+    pub fn sizes(&self) -> Vec<u64> {
+        let mut sizes: Vec<u64> = self
+            .nodes
+            .iter()
+            .map(|node| match node.kind {
+                NodeKind::File { size } => size,
+                NodeKind::Dir { .. } => 0,
+            })
+            .collect();
+
+        // Children come after their parents, so by going backwards every size
+        // is complete by the time it's added to the parent's one
+        for id in (1..self.nodes.len()).rev() {
+            if let Some(parent) = self.nodes[id].parent {
+                sizes[parent] += sizes[id];
+            }
+        }
+
+        sizes
+    }
+
+    /// Maps paths of directories to their total sizes.
+    pub fn dir_sizes(&self) -> HashMap<String, u64> {
+        let sizes = self.sizes();
+
+        self.ids()
+            .filter(|&id| self.is_dir(id))
+            .map(|id| (self.path(id), sizes[id]))
+            .collect()
+    }
+
+    fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        match &self.nodes[dir].kind {
+            NodeKind::Dir { children, .. } => children.get(name).copied(),
+            NodeKind::File { .. } => None,
+        }
+    }
+
+    fn add(&mut self, parent: NodeId, name: &str, entry: Entry) -> NodeId {
+        let id = self.nodes.len();
+        let kind = match entry {
+            Entry::Dir => NodeKind::Dir {
+                children: BTreeMap::new(),
+                listed: false,
+            },
+            Entry::File(size) => NodeKind::File { size },
+        };
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(parent),
+            kind,
+        });
+
+        if let NodeKind::Dir { children, .. } = &mut self.nodes[parent].kind {
+            children.insert(name.to_string(), id);
+        }
+
+        id
+    }
+
+    fn entry(&self, id: NodeId) -> Entry {
+        match self.nodes[id].kind {
+            NodeKind::File { size } => Entry::File(size),
+            NodeKind::Dir { .. } => Entry::Dir,
+        }
+    }
+
+    /// Resolves `path` relative to `cwd` the way `cd` does. Directories which
+    /// weren't listed yet are created along the way.
+    fn change_dir(&mut self, cwd: NodeId, path: &str) -> Result<NodeId, anyhow::Error> {
+        let mut current = if path.starts_with('/') { ROOT } else { cwd };
+
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            current = match name {
+                "." => current,
+                // Just like in a shell, going up from the root stays there
+                ".." => self.nodes[current].parent.unwrap_or(ROOT),
+                _ => match self.child(current, name) {
+                    Some(child) if self.is_dir(child) => child,
+                    Some(file) => bail!("{} is not a directory", self.path(file)),
+                    None => {
+                        if let NodeKind::Dir { listed: true, .. } = self.nodes[current].kind {
+                            bail!("no directory {} in {}", name, self.path(current));
+                        }
+
+                        self.add(current, name, Entry::Dir)
+                    }
+                },
+            };
+        }
+
+        Ok(current)
+    }
+
+    /// Records `ls` output of a directory, which has to agree with whatever
+    /// is already known about it.
+    fn list(&mut self, dir: NodeId, entries: &[(&str, Entry)]) -> Result<(), anyhow::Error> {
+        let mut listing: BTreeMap<&str, Entry> = BTreeMap::new();
+        for &(name, entry) in entries {
+            if listing.insert(name, entry).is_some() {
+                bail!("{} is listed twice", name);
+            }
+        }
+
+        let NodeKind::Dir { listed, .. } = self.nodes[dir].kind else {
+            unreachable!("only directories can be current working directory");
+        };
+
+        for child in self.children(dir) {
+            let name = self.nodes[child].name.as_str();
+            match listing.get(name) {
+                Some(&entry) if entry == self.entry(child) => {}
+                Some(_) => bail!("{} changed since it was seen before", self.path(child)),
+                None if listed => bail!("{} is missing from listing", self.path(child)),
+                None => bail!(
+                    "{} is missing from listing, but it was entered before",
+                    self.path(child)
+                ),
+            }
+        }
+
+        for (name, entry) in listing {
+            if self.child(dir, name).is_none() {
+                if listed {
+                    bail!("{} appeared since previous listing", name);
+                }
+
+                self.add(dir, name, entry);
+            }
+        }
+
+        if let NodeKind::Dir { listed, .. } = &mut self.nodes[dir].kind {
+            *listed = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Filesystem {
+    type Err = anyhow::Error;
+
+    /// Replays transcript of `$ cd PATH` and `$ ls` commands and their output,
+    /// starting in the root directory.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fs = Filesystem {
+            nodes: vec![Node {
+                name: String::new(),
+                parent: None,
+                kind: NodeKind::Dir {
+                    children: BTreeMap::new(),
+                    listed: false,
+                },
+            }],
+        };
+        let mut cwd = ROOT;
+        // Line number of the `ls` command in progress and its output so far
+        let mut listing: Option<(usize, Vec<(&str, Entry)>)> = None;
+
+        for (line_number, line) in (1..).zip(s.lines()) {
+            let Some(command) = line.strip_prefix("$ ") else {
+                let (_, entries) = listing
+                    .as_mut()
+                    .ok_or_else(|| anyhow!("line {}: output without `ls`", line_number))?;
+                let (entry, name) = line.split_once(' ').ok_or_else(|| {
+                    anyhow!("line {}: couldn't split '{}' at ' '", line_number, line)
+                })?;
+                if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+                    bail!("line {}: invalid name '{}'", line_number, name);
+                }
+
+                entries.push((
+                    name,
+                    entry
+                        .parse()
+                        .with_context(|| format!("line {}", line_number))?,
+                ));
+                continue;
+            };
+
+            if let Some((ls_line_number, entries)) = listing.take() {
+                fs.list(cwd, &entries)
+                    .with_context(|| format!("inconsistent listing on line {}", ls_line_number))?;
+            }
+
+            match command.split_once(' ') {
+                Some(("cd", path)) => {
+                    cwd = fs
+                        .change_dir(cwd, path)
+                        .with_context(|| format!("line {}: cd {}", line_number, path))?
+                }
+                None if command == "ls" => listing = Some((line_number, vec![])),
+                _ => bail!("line {}: unknown command '{}'", line_number, command),
+            }
+        }
+
+        if let Some((ls_line_number, entries)) = listing {
+            fs.list(cwd, &entries)
+                .with_context(|| format!("inconsistent listing on line {}", ls_line_number))?;
+        }
+
+        Ok(fs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes_of(transcript: &str) -> Vec<(String, u64)> {
+        let fs: Filesystem = transcript.parse().unwrap();
+        let mut sizes: Vec<_> = fs.dir_sizes().into_iter().collect();
+        sizes.sort();
+
+        sizes
+    }
+
+    #[test]
+    fn test_absolute_and_multi_segment_cd() {
+        let transcript = "\
+$ cd /a/b
+$ ls
+10 x
+$ cd ../../c/./d
+$ ls
+20 y
+$ cd /
+$ ls
+dir a
+dir c
+1 z";
+
+        assert_eq!(
+            sizes_of(transcript),
+            vec![
+                ("/".to_string(), 31),
+                ("/a".to_string(), 10),
+                ("/a/b".to_string(), 10),
+                ("/c".to_string(), 20),
+                ("/c/d".to_string(), 20),
+            ]
+        );
+
+        // Going up from the root stays there
+        assert_eq!(
+            sizes_of("$ cd ..\n$ cd a\n$ ls\n5 f"),
+            sizes_of("$ cd a\n$ ls\n5 f")
+        );
+    }
+
+    #[test]
+    fn test_repeated_ls() {
+        let transcript = "\
+$ cd /
+$ ls
+dir a
+100 b
+$ cd a
+$ ls
+7 c
+$ cd ..
+$ ls
+100 b
+dir a";
+
+        assert_eq!(
+            sizes_of(transcript),
+            vec![("/".to_string(), 107), ("/a".to_string(), 7)]
+        );
+
+        let fs: Filesystem = transcript.parse().unwrap();
+        assert_eq!(fs.ids().len(), 4);
+        assert_eq!(fs.path(3), "/a/c");
+    }
+
+    #[test]
+    fn test_inconsistent_listings() {
+        for transcript in [
+            // File changed size
+            "$ ls\n100 b\n$ ls\n101 b",
+            // File disappeared
+            "$ ls\n100 b\ndir a\n$ ls\ndir a",
+            // File appeared
+            "$ ls\n100 b\n$ ls\n100 b\n1 c",
+            // Directory turned into file
+            "$ ls\ndir a\n$ ls\n1 a",
+            // Entered directory missing from listing
+            "$ cd a\n$ cd ..\n$ ls\n1 b",
+            // Same name listed twice
+            "$ ls\n1 b\n2 b",
+        ] {
+            let err = transcript.parse::<Filesystem>().unwrap_err();
+            assert!(
+                format!("{:#}", err).contains("inconsistent listing"),
+                "{}",
+                transcript
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_transcripts() {
+        for transcript in [
+            // cd into directory which is known not to exist
+            "$ ls\ndir a\n$ cd b",
+            // cd into file
+            "$ ls\n1 a\n$ cd a",
+            "1 a",
+            "$ ls\nabc a",
+            "$ ls\n1 a/b",
+            "$ rm -rf /",
+        ] {
+            assert!(transcript.parse::<Filesystem>().is_err(), "{}", transcript);
+        }
+    }
+}
@@ -1,88 +1,100 @@
-use std::collections::HashMap;
-
-use anyhow::Context;
-use common::{get_arg, read_file_to_string};
-
-const ROOT: &str = "";
-
-/// Constructs mapping of directory paths to their total sizes
-fn dir_walk<'a>(commands: impl IntoIterator<Item = &'a str>) -> HashMap<String, u64> {
-    // Current working directory
-    let mut cwd: Vec<String> = vec![];
-    // Directories and their sizes
-    let mut dirs: HashMap<String, u64> = HashMap::new();
-
-    for command in commands {
-        if command.starts_with("$ cd") {
-            // We leverage the property of input that we only move up or down
-            // (out/in) by one directory and never jump around file-system
-            match &command[5..] {
-                "/" => {
-                    // Represent root directory as empty string, so `dirs` map
-                    // keys take familiar form of `/some/nested/dir`
-                    cwd = vec![ROOT.to_string()];
-                }
-                ".." => {
-                    cwd.pop();
-                }
-                other => {
-                    cwd.push(other.to_string());
-                }
-            }
-        } else if command.starts_with("$ ls") || command.starts_with("dir") {
-            // No-op
-            continue;
-        } else {
-            // Size & file name
-            let (size, _file_name) = command.split_once(' ').unwrap();
-            let size = size.parse::<u64>().unwrap();
-
-            // Update totals up the tree
-            for i in 0..cwd.len() {
-                // Inefficient but keeps solution clearer (could be done once
-                // per `ls`)
-                let key = cwd[0..=i].join("/").to_string();
-
-                dirs.entry(key).and_modify(|a| *a += size).or_insert(size);
-            }
-        }
-    }
+use anyhow::{anyhow, Context};
+use common::{
+    get_arg, get_option,
+    profiling::profile,
+    progress::{cancel_on_ctrl_c, get_budget, Progress},
+    read_file_to_string,
+};
+use deletion::{plan_exact, plan_greedy, Mode};
+use fs::{Filesystem, ROOT};
+use query::{
+    find_smallest_directory_that_frees_up_enough_space, space_to_free_up,
+    sum_sizes_of_small_directories, Query, DISK_SIZE, SMALL_DIR_MAX_SIZE, SPACE_NEEDED,
+};
+
+mod deletion;
+mod fs;
+mod query;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
-    dirs
-}
+fn main() -> Result<(), anyhow::Error> {
+    let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
+    let input_string = read_file_to_string(&input_file_path)?;
+    let fs: Filesystem = profile("directory walk", || input_string.parse())?;
 
-fn sum_sizes_of_small_directories(dirs: &HashMap<String, u64>) -> u64 {
-    dirs.iter()
-        .filter_map(|(_, &size)| if size <= 100000 { Some(size) } else { None })
-        .sum()
-}
+    if let Some(query) = get_option("--query") {
+        let query: Query = query.parse().context("couldn't parse --query")?;
+        print!("{}", profile("query", || query.run(&fs))?);
+
+        return Ok(());
+    }
 
-fn find_smallest_directory_that_frees_up_enough_space(dirs: &HashMap<String, u64>) -> Option<u64> {
-    let total_disk_space = 70000000;
-    let current_free_space = total_disk_space - dirs[ROOT];
-    let space_needed_to_be_freed = 30000000 - current_free_space;
-
-    dirs.iter()
-        .filter_map(|(_, &size)| {
-            if size >= space_needed_to_be_freed {
-                Some(size)
-            } else {
-                None
+    if let Some(mode) = get_option("--delete") {
+        let mode: Mode = mode.parse().context("couldn't parse --delete")?;
+        let disk_size = match get_option("--disk") {
+            Some(disk_size) => disk_size.parse().context("couldn't parse --disk")?,
+            None => DISK_SIZE,
+        };
+        let space_needed = match get_option("--need") {
+            Some(space_needed) => space_needed.parse().context("couldn't parse --need")?,
+            None => SPACE_NEEDED,
+        };
+
+        let sizes = fs.sizes();
+        let to_free = space_to_free_up(sizes[ROOT], disk_size, space_needed);
+        cancel_on_ctrl_c()?;
+        let progress = Progress::hidden(0).with_budget(get_budget()?);
+        let outcome = profile("deletion plan", || match mode {
+            Mode::Exact => plan_exact(&fs, &sizes, to_free, &progress),
+            Mode::Greedy => progress.finish(plan_greedy(&fs, &sizes, to_free)),
+        });
+
+        let is_partial = outcome.is_partial();
+        match outcome.into_inner() {
+            Some(deletion) => {
+                println!(
+                    "Deleting {} nodes frees up {} of {} needed{}:",
+                    deletion.nodes.len(),
+                    deletion.freed,
+                    to_free,
+                    if is_partial {
+                        " (best found within time budget)"
+                    } else {
+                        ""
+                    }
+                );
+                for id in deletion.nodes {
+                    println!("{}\t{}", sizes[id], fs.path(id));
+                }
             }
-        })
-        .min()
-}
+            None => println!("Deleting everything doesn't free up {}", to_free),
+        }
 
-fn main() -> Result<(), anyhow::Error> {
-    let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
-    let input_string = read_file_to_string(&input_file_path)?;
+        return Ok(());
+    }
 
-    let dirs = dir_walk(input_string.lines());
+    // Root used to be keyed by "" rather than "/", so dumps of older
+    // directory sizes are kept apart
+    #[cfg(feature = "serde")]
+    let dirs = common::serialization::cached("dirs-v2", || fs.dir_sizes())?;
+    #[cfg(not(feature = "serde"))]
+    let dirs = fs.dir_sizes();
 
-    println!("Part 1 solution: {}", sum_sizes_of_small_directories(&dirs));
+    println!(
+        "Part 1 solution: {}",
+        profile("part 1", || {
+            sum_sizes_of_small_directories(&dirs, SMALL_DIR_MAX_SIZE)
+        })
+    );
     println!(
         "Part 2 solution: {}",
-        find_smallest_directory_that_frees_up_enough_space(&dirs).unwrap()
+        profile("part 2", || {
+            find_smallest_directory_that_frees_up_enough_space(&dirs, DISK_SIZE, SPACE_NEEDED)
+        })?
+        .ok_or_else(|| anyhow!("no directory frees up enough space"))?
     );
 
     Ok(())
@@ -90,6 +102,8 @@ fn main() -> Result<(), anyhow::Error> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
 
     const TEST_INPUT: &str = "\
@@ -118,33 +132,15 @@ $ ls
 7214296 k";
 
     #[test]
-    fn test_dir_walk() {
-        let dirs = dir_walk(TEST_INPUT.lines());
-
+    fn test_dir_sizes() {
         assert_eq!(
-            dirs,
+            TEST_INPUT.parse::<Filesystem>().unwrap().dir_sizes(),
             HashMap::from([
-                ("".to_string(), 48381165),
+                ("/".to_string(), 48381165),
                 ("/a".to_string(), 94853),
                 ("/a/e".to_string(), 584),
                 ("/d".to_string(), 24933642),
             ])
         );
     }
-
-    #[test]
-    fn test_sum_sizes_of_small_directories() {
-        let dirs = dir_walk(TEST_INPUT.lines());
-        let sum = sum_sizes_of_small_directories(&dirs);
-
-        assert_eq!(sum, 95437);
-    }
-
-    #[test]
-    fn test_find_smallest_directory_that_frees_up_enough_space() {
-        let dirs = dir_walk(TEST_INPUT.lines());
-        let size = find_smallest_directory_that_frees_up_enough_space(&dirs).unwrap();
-
-        assert_eq!(size, 24933642);
-    }
 }
@@ -0,0 +1,529 @@
+//! Shell-like queries over reconstructed filesystem: `tree`, `du`, `find` and
+//! friends.
+
+use std::{cmp::Ordering, collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::fs::{Filesystem, NodeId, ROOT};
+
+pub const SMALL_DIR_MAX_SIZE: u64 = 100000;
+pub const DISK_SIZE: u64 = 70000000;
+pub const SPACE_NEEDED: u64 = 30000000;
+
+/// Sums sizes of directories of at most `max_size`.
+pub fn sum_sizes_of_small_directories(dirs: &HashMap<String, u64>, max_size: u64) -> u64 {
+    dirs.values().filter(|&&size| size <= max_size).sum()
+}
+
+/// How much more space has to be freed, for `space_needed` to be free on disk
+/// of `disk_size` with `used` space taken.
+pub fn space_to_free_up(used: u64, disk_size: u64, space_needed: u64) -> u64 {
+    space_needed.saturating_sub(disk_size.saturating_sub(used))
+}
+
+/// Finds size of the smallest directory, deleting which leaves at least
+/// `space_needed` free space on disk of `disk_size`. Fails when `dirs` lack the
+/// root directory, e.g. when they were dumped by an older version.
+pub fn find_smallest_directory_that_frees_up_enough_space(
+    dirs: &HashMap<String, u64>,
+    disk_size: u64,
+    space_needed: u64,
+) -> Result<Option<u64>, anyhow::Error> {
+    let used = *dirs
+        .get("/")
+        .ok_or_else(|| anyhow!("no size of root directory '/'"))?;
+    let space_needed_to_be_freed = space_to_free_up(used, disk_size, space_needed);
+
+    Ok(dirs
+        .values()
+        .copied()
+        .filter(|&size| size >= space_needed_to_be_freed)
+        .min())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeType {
+    File,
+    Dir,
+}
+
+impl FromStr for NodeType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "f" => Ok(Self::File),
+            "d" => Ok(Self::Dir),
+            _ => bail!("unknown type: {} (expected 'f' or 'd')", s),
+        }
+    }
+}
+
+/// Conditions nodes have to meet, all of them optional.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    /// Glob pattern for names, with `*` matching any run of characters and
+    /// `?` any single character
+    pub name: Option<String>,
+    /// How total size compares to the given one
+    pub size: Option<(Ordering, u64)>,
+    pub node_type: Option<NodeType>,
+}
+
+/// Checks if `name` matches glob `pattern`, backtracking only to the most
+/// recent `*`.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Position in pattern right after the last `*`, and in name where it
+    // would start matching
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => {
+                // Let the last `*` match one more character
+                let Some((after_star, matched_from)) = star else {
+                    return false;
+                };
+                star = Some((after_star, matched_from + 1));
+                p = after_star;
+                n = matched_from + 1;
+            }
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl Filter {
+    fn matches(&self, fs: &Filesystem, sizes: &[u64], id: NodeId) -> bool {
+        let node_type = match fs.is_dir(id) {
+            true => NodeType::Dir,
+            false => NodeType::File,
+        };
+
+        self.name
+            .as_ref()
+            .is_none_or(|pattern| glob_matches(pattern, &fs.node(id).name))
+            && self
+                .size
+                .is_none_or(|(ordering, size)| sizes[id].cmp(&size) == ordering)
+            && self.node_type.is_none_or(|t| t == node_type)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    /// Whole tree with sizes
+    Tree,
+    /// Total sizes of directories, at most `max_depth` levels below the root
+    Du { max_depth: Option<usize> },
+    /// Paths of nodes matching filter
+    Find(Filter),
+    /// The largest nodes matching filter
+    Top { count: usize, filter: Filter },
+    /// Whole tree as JSON
+    Json,
+    /// Sum of sizes of directories of at most `max_size`
+    SmallDirs { max_size: u64 },
+    /// Size of the smallest directory to delete to have enough free space
+    FreeSpace { disk_size: u64, space_needed: u64 },
+}
+
+/// Parses `-key value` pairs, with keys limited to `allowed` ones.
+fn parse_options<'a>(
+    args: &[&'a str],
+    allowed: &[&str],
+) -> Result<HashMap<&'a str, &'a str>, anyhow::Error> {
+    if !args.len().is_multiple_of(2) {
+        bail!("expected options as pairs of `-key value`");
+    }
+
+    args.chunks(2)
+        .map(|pair| {
+            let key = pair[0]
+                .strip_prefix('-')
+                .filter(|key| allowed.contains(key))
+                .ok_or_else(|| anyhow!("unknown option: {}", pair[0]))?;
+
+            Ok((key, pair[1]))
+        })
+        .collect()
+}
+
+fn parse_option<T>(options: &HashMap<&str, &str>, key: &str) -> Result<Option<T>, anyhow::Error>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    options
+        .get(key)
+        .map(|value| value.parse())
+        .transpose()
+        .with_context(|| format!("couldn't parse -{}", key))
+}
+
+/// Parses size comparison like `find -size` does: `+N` for larger than `N`,
+/// `-N` for smaller than `N`, and `N` for exactly `N`.
+fn parse_size(s: &str) -> Result<(Ordering, u64), anyhow::Error> {
+    let (ordering, size) = if let Some(size) = s.strip_prefix('+') {
+        (Ordering::Greater, size)
+    } else if let Some(size) = s.strip_prefix('-') {
+        (Ordering::Less, size)
+    } else {
+        (Ordering::Equal, s)
+    };
+
+    Ok((ordering, size.parse().context("couldn't parse -size")?))
+}
+
+fn parse_filter(options: &HashMap<&str, &str>) -> Result<Filter, anyhow::Error> {
+    Ok(Filter {
+        name: options.get("name").map(|name| name.to_string()),
+        size: options
+            .get("size")
+            .map(|size| parse_size(size))
+            .transpose()?,
+        node_type: options
+            .get("type")
+            .map(|node_type| node_type.parse())
+            .transpose()?,
+    })
+}
+
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    /// Parses query like `du -d 1` or `find -name *.txt -size +1000 -type f`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let Some((&command, args)) = words.split_first() else {
+            bail!("empty query");
+        };
+
+        let query = match command {
+            "tree" => {
+                parse_options(args, &[])?;
+                Self::Tree
+            }
+            "du" => Self::Du {
+                max_depth: parse_option(&parse_options(args, &["d"])?, "d")?,
+            },
+            "find" => Self::Find(parse_filter(&parse_options(
+                args,
+                &["name", "size", "type"],
+            )?)?),
+            "top" => {
+                let options = parse_options(args, &["n", "name", "size", "type"])?;
+
+                Self::Top {
+                    count: parse_option(&options, "n")?.unwrap_or(10),
+                    filter: parse_filter(&options)?,
+                }
+            }
+            "json" => {
+                parse_options(args, &[])?;
+                Self::Json
+            }
+            "small-dirs" => Self::SmallDirs {
+                max_size: parse_option(&parse_options(args, &["max"])?, "max")?
+                    .unwrap_or(SMALL_DIR_MAX_SIZE),
+            },
+            "free-space" => {
+                let options = parse_options(args, &["disk", "need"])?;
+
+                Self::FreeSpace {
+                    disk_size: parse_option(&options, "disk")?.unwrap_or(DISK_SIZE),
+                    space_needed: parse_option(&options, "need")?.unwrap_or(SPACE_NEEDED),
+                }
+            }
+            _ => bail!("unknown query: {}", command),
+        };
+
+        Ok(query)
+    }
+}
+
+fn display_name(fs: &Filesystem, id: NodeId) -> &str {
+    match id {
+        ROOT => "/",
+        _ => &fs.node(id).name,
+    }
+}
+
+/// Renders tree the way puzzle description does, with sizes of directories.
+fn render_tree(fs: &Filesystem, sizes: &[u64], id: NodeId, depth: usize, out: &mut String) {
+    let kind = match fs.is_dir(id) {
+        true => "dir",
+        false => "file",
+    };
+    out.push_str(&format!(
+        "{}- {} ({}, size={})\n",
+        "  ".repeat(depth),
+        display_name(fs, id),
+        kind,
+        sizes[id]
+    ));
+
+    for child in fs.children(id) {
+        render_tree(fs, sizes, child, depth + 1, out);
+    }
+}
+
+/// Lists directories `du`-style, with subdirectories before their parents.
+fn render_du(
+    fs: &Filesystem,
+    sizes: &[u64],
+    id: NodeId,
+    depth: usize,
+    max_depth: Option<usize>,
+    out: &mut String,
+) {
+    for child in fs.children(id).filter(|&child| fs.is_dir(child)) {
+        render_du(fs, sizes, child, depth + 1, max_depth, out);
+    }
+
+    if max_depth.is_none_or(|max_depth| depth <= max_depth) {
+        out.push_str(&format!("{}\t{}\n", sizes[id], fs.path(id)));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct JsonNode<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<JsonNode<'a>>>,
+}
+
+#[cfg(feature = "serde")]
+fn to_json_node<'a>(fs: &'a Filesystem, sizes: &[u64], id: NodeId) -> JsonNode<'a> {
+    let children = fs.is_dir(id).then(|| {
+        fs.children(id)
+            .map(|child| to_json_node(fs, sizes, child))
+            .collect()
+    });
+
+    JsonNode {
+        name: display_name(fs, id),
+        kind: if fs.is_dir(id) { "dir" } else { "file" },
+        size: sizes[id],
+        children,
+    }
+}
+
+impl Query {
+    /// Runs query, returning its output.
+    pub fn run(&self, fs: &Filesystem) -> Result<String, anyhow::Error> {
+        let sizes = fs.sizes();
+        let mut out = String::new();
+
+        match self {
+            Self::Tree => render_tree(fs, &sizes, ROOT, 0, &mut out),
+            Self::Du { max_depth } => render_du(fs, &sizes, ROOT, 0, *max_depth, &mut out),
+            Self::Find(filter) => {
+                let mut paths: Vec<String> = fs
+                    .ids()
+                    .filter(|&id| filter.matches(fs, &sizes, id))
+                    .map(|id| fs.path(id))
+                    .collect();
+                paths.sort();
+
+                for path in paths {
+                    out.push_str(&format!("{}\n", path));
+                }
+            }
+            Self::Top { count, filter } => {
+                let mut ids: Vec<NodeId> = fs
+                    .ids()
+                    .filter(|&id| filter.matches(fs, &sizes, id))
+                    .collect();
+                ids.sort_by_key(|&id| std::cmp::Reverse(sizes[id]));
+
+                for id in ids.into_iter().take(*count) {
+                    out.push_str(&format!("{:>10} {}\n", sizes[id], fs.path(id)));
+                }
+            }
+            Self::Json => {
+                #[cfg(feature = "serde")]
+                {
+                    out = serde_json::to_string_pretty(&to_json_node(fs, &sizes, ROOT))?;
+                    out.push('\n');
+                }
+                #[cfg(not(feature = "serde"))]
+                bail!("JSON export requires `serde` feature");
+            }
+            Self::SmallDirs { max_size } => {
+                let sum = sum_sizes_of_small_directories(&fs.dir_sizes(), *max_size);
+                out = format!("{}\n", sum);
+            }
+            Self::FreeSpace {
+                disk_size,
+                space_needed,
+            } => {
+                let size = find_smallest_directory_that_frees_up_enough_space(
+                    &fs.dir_sizes(),
+                    *disk_size,
+                    *space_needed,
+                )?
+                .ok_or_else(|| anyhow!("no directory frees up enough space"))?;
+                out = format!("{}\n", size);
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    fn run(query: &str) -> String {
+        let fs: Filesystem = TRANSCRIPT.parse().unwrap();
+
+        query.parse::<Query>().unwrap().run(&fs).unwrap()
+    }
+
+    #[test]
+    fn test_sum_sizes_of_small_directories() {
+        let dirs = TRANSCRIPT.parse::<Filesystem>().unwrap().dir_sizes();
+
+        assert_eq!(
+            sum_sizes_of_small_directories(&dirs, SMALL_DIR_MAX_SIZE),
+            95437
+        );
+        assert_eq!(sum_sizes_of_small_directories(&dirs, 1000), 584);
+        assert_eq!(run("small-dirs -max 1000"), "584\n");
+    }
+
+    #[test]
+    fn test_find_smallest_directory_that_frees_up_enough_space() {
+        let dirs = TRANSCRIPT.parse::<Filesystem>().unwrap().dir_sizes();
+
+        assert_eq!(
+            find_smallest_directory_that_frees_up_enough_space(&dirs, DISK_SIZE, SPACE_NEEDED)
+                .unwrap(),
+            Some(24933642)
+        );
+        assert_eq!(run("free-space -need 21700000"), "94853\n");
+
+        // Root used to be keyed by ""
+        let old_dirs = HashMap::from([("".to_string(), 48381165)]);
+        let err = find_smallest_directory_that_frees_up_enough_space(&old_dirs, 0, 0).unwrap_err();
+        assert_eq!(err.to_string(), "no size of root directory '/'");
+    }
+
+    #[test]
+    fn test_tree() {
+        assert_eq!(
+            run("tree"),
+            "\
+- / (dir, size=48381165)
+  - a (dir, size=94853)
+    - e (dir, size=584)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir, size=24933642)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+"
+        );
+    }
+
+    #[test]
+    fn test_du() {
+        assert_eq!(run("du -d 1"), "94853\t/a\n24933642\t/d\n48381165\t/\n");
+        assert_eq!(run("du").lines().next(), Some("584\t/a/e"));
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(
+            run("find -name *.*"),
+            "/a/h.lst\n/b.txt\n/c.dat\n/d/d.ext\n/d/d.log\n"
+        );
+        assert_eq!(run("find -name d* -type f"), "/d/d.ext\n/d/d.log\n");
+        assert_eq!(run("find -name ? -size -10000"), "/a/e\n/a/e/i\n/a/g\n");
+        assert_eq!(run("find -type d -size +25000000"), "/\n");
+        assert_eq!(run("find -size 584"), "/a/e\n/a/e/i\n");
+    }
+
+    #[test]
+    fn test_top() {
+        assert_eq!(
+            run("top -n 2 -type f"),
+            "  14848514 /b.txt\n   8504156 /c.dat\n"
+        );
+        assert_eq!(run("top").lines().count(), 10);
+    }
+
+    #[test]
+    fn test_parse_query() {
+        for query in [
+            "",
+            "ls",
+            "du -d",
+            "du -x 1",
+            "find -size +big",
+            "top -type x",
+        ] {
+            assert!(query.parse::<Query>().is_err(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("a*b?c*", "axxbyczz"));
+        assert!(glob_matches("*.log", "d.log"));
+        assert!(!glob_matches("*.log", "d.log.gz"));
+        assert!(!glob_matches("a?", "a"));
+    }
+}
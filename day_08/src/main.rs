@@ -1,9 +1,23 @@
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+#[cfg(not(feature = "serde"))]
+use common::read_problem;
+#[cfg(feature = "serde")]
+use common::serialization::read_problem;
+use common::{get_arg, get_option, has_flag, profiling::profile};
+use sightlines::Sightlines;
+use viewshed::{Observer, Rays, Viewshed};
+
+mod sightlines;
+mod viewshed;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Problem {
     trees: Vec<Vec<u8>>,
 }
@@ -29,7 +43,7 @@ impl FromStr for Problem {
     }
 }
 
-fn visible_trees_map(trees: &Vec<Vec<u8>>) -> Vec<Vec<bool>> {
+fn visible_trees_map(trees: &[Vec<u8>]) -> Vec<Vec<bool>> {
     let rows_count = trees.len();
     let columns_count = trees[0].len();
 
@@ -102,7 +116,7 @@ fn visible_trees_map(trees: &Vec<Vec<u8>>) -> Vec<Vec<bool>> {
     visible_trees
 }
 
-fn count_visible_trees(visible_trees: &Vec<Vec<bool>>) -> u64 {
+fn count_visible_trees(visible_trees: &[Vec<bool>]) -> u64 {
     visible_trees
         .iter()
         .map(|v| v.iter().filter(|&b| *b).count() as u64)
@@ -138,7 +152,7 @@ fn compute_viewing_distances(tree_line: &[u8]) -> Vec<u8> {
     scores
 }
 
-fn compute_scenic_scores(trees: &Vec<Vec<u8>>) -> Vec<Vec<u64>> {
+fn compute_scenic_scores(trees: &[Vec<u8>]) -> Vec<Vec<u64>> {
     let rows_count = trees.len();
     let columns_count = trees[0].len();
 
@@ -176,7 +190,7 @@ fn compute_scenic_scores(trees: &Vec<Vec<u8>>) -> Vec<Vec<u64>> {
 
     let southward_viewing_distances = trees_transposed
         .iter()
-        .map(|column| compute_viewing_distances(&column))
+        .map(|column| compute_viewing_distances(column))
         .collect::<Vec<_>>();
 
     let northward_viewing_distances = trees_transposed
@@ -203,19 +217,57 @@ fn compute_scenic_scores(trees: &Vec<Vec<u8>>) -> Vec<Vec<u64>> {
     scenic_scores
 }
 
-fn find_max(vv: &Vec<Vec<u64>>) -> Option<u64> {
+fn find_max(vv: &[Vec<u64>]) -> Option<u64> {
     vv.iter().filter_map(|v| v.iter().max()).max().copied()
 }
 
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
-    let input_string = read_file_to_string(&input_file_path)?;
-    let Problem { trees } = input_string.parse()?;
-    let visible_trees = visible_trees_map(&trees);
+    let Problem { trees } = read_problem(&input_file_path)?;
+
+    if let Some(position) = get_option("--viewshed") {
+        let height = match get_option("--height") {
+            Some(height) => Some(height.parse().context("couldn't parse --height")?),
+            None => None,
+        };
+        let rays: Rays = match get_option("--rays") {
+            Some(rays) => rays.parse().context("couldn't parse --rays")?,
+            None => Rays::Compass,
+        };
+        let observer =
+            Observer::new(&trees, &position, height).context("couldn't parse --viewshed")?;
+
+        let viewshed = profile("viewshed", || Viewshed::new(&trees, observer, &rays));
+        print!("{}", viewshed.render(&trees));
+        println!("Visible trees: {}", viewshed.count());
+
+        return Ok(());
+    }
+
+    // Directional passes over every row and column, kept as a reference
+    if has_flag("--naive") {
+        let visible_trees = profile("part 1", || visible_trees_map(&trees));
+        println!("Part 1 solution: {}", count_visible_trees(&visible_trees));
+
+        let scenic_scores = profile("part 2", || compute_scenic_scores(&trees));
+        println!("Part 2 solution: {}", find_max(&scenic_scores).unwrap());
+
+        return Ok(());
+    }
+
+    let sightlines = profile("sightlines", || Sightlines::new(&trees));
 
-    println!("Part 1 solution: {}", count_visible_trees(&visible_trees));
+    println!(
+        "Part 1 solution: {}",
+        profile("part 1", || count_visible_trees(&sightlines.visible))
+    );
 
-    let scenic_scores = compute_scenic_scores(&trees);
+    #[cfg(feature = "serde")]
+    let scenic_scores = common::serialization::cached("scenic-scores", || {
+        profile("part 2", || sightlines.scenic_scores())
+    })?;
+    #[cfg(not(feature = "serde"))]
+    let scenic_scores = profile("part 2", || sightlines.scenic_scores());
 
     println!("Part 2 solution: {}", find_max(&scenic_scores).unwrap());
 
@@ -0,0 +1,171 @@
+//! Visibility from the edges and viewing distances of all trees at once, with
+//! a monotonic stack over every row and column.
+
+use rayon::prelude::*;
+
+pub const NORTH: usize = 0;
+pub const EAST: usize = 1;
+pub const SOUTH: usize = 2;
+pub const WEST: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sightlines {
+    /// Whether tree is visible from outside of the forest
+    pub visible: Vec<Vec<bool>>,
+    /// Viewing distances towards [`NORTH`], [`EAST`], [`SOUTH`] and [`WEST`]
+    pub distances: Vec<Vec<[usize; 4]>>,
+}
+
+/// Whether tree is visible from one side and how far it can see towards it.
+type Seen = (bool, usize);
+
+/// Scans line of trees from its start, finding whether each tree is visible
+/// from the start and how far it can see towards it.
+///
+/// Stack keeps trees which aren't hidden behind a later, at least as tall one,
+/// so it's strictly decreasing in height and every tree is pushed and popped
+/// at most once.
+fn scan(heights: impl Iterator<Item = u8>) -> Vec<Seen> {
+    let mut stack: Vec<(usize, u8)> = vec![];
+
+    heights
+        .enumerate()
+        .map(|(position, height)| {
+            while stack.last().is_some_and(|&(_, top)| top < height) {
+                stack.pop();
+            }
+
+            // Blocking tree is the first one at least as tall, otherwise view
+            // reaches the edge
+            let seen = match stack.last() {
+                Some(&(blocking, _)) => (false, position - blocking),
+                None => (true, position),
+            };
+            stack.push((position, height));
+
+            seen
+        })
+        .collect()
+}
+
+/// Scans line both ways, returning results of [`scan`] from the start and from
+/// the end, both in order of the line.
+fn scan_both_ways(line: &[u8]) -> (Vec<Seen>, Vec<Seen>) {
+    let forward = scan(line.iter().copied());
+    let mut backward = scan(line.iter().rev().copied());
+    backward.reverse();
+
+    (forward, backward)
+}
+
+impl Sightlines {
+    /// Computes sightlines in O(rows × columns) time, scanning rows and columns
+    /// in parallel.
+    pub fn new(trees: &[Vec<u8>]) -> Self {
+        let rows_count = trees.len();
+        let columns_count = trees.first().map_or(0, |row| row.len());
+
+        let rows: Vec<_> = trees.par_iter().map(|row| scan_both_ways(row)).collect();
+        let columns: Vec<_> = (0..columns_count)
+            .into_par_iter()
+            .map(|col| {
+                let column: Vec<u8> = trees.iter().map(|row| row[col]).collect();
+
+                scan_both_ways(&column)
+            })
+            .collect();
+
+        let mut visible = vec![vec![false; columns_count]; rows_count];
+        let mut distances = vec![vec![[0; 4]; columns_count]; rows_count];
+        for row in 0..rows_count {
+            for col in 0..columns_count {
+                let (from_west, from_east) = (&rows[row].0[col], &rows[row].1[col]);
+                let (from_north, from_south) = (&columns[col].0[row], &columns[col].1[row]);
+
+                visible[row][col] = from_north.0 || from_east.0 || from_south.0 || from_west.0;
+                distances[row][col][NORTH] = from_north.1;
+                distances[row][col][EAST] = from_east.1;
+                distances[row][col][SOUTH] = from_south.1;
+                distances[row][col][WEST] = from_west.1;
+            }
+        }
+
+        Self { visible, distances }
+    }
+
+    /// Products of viewing distances in all four directions.
+    pub fn scenic_scores(&self) -> Vec<Vec<u64>> {
+        self.distances
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|distances| distances.iter().map(|&d| d as u64).product())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{compute_scenic_scores, visible_trees_map};
+
+    #[test]
+    fn test_scan() {
+        assert_eq!(
+            scan([2, 5, 5, 1, 2].into_iter()),
+            vec![(true, 0), (true, 1), (false, 1), (false, 1), (false, 2)]
+        );
+        assert_eq!(
+            scan_both_ways(&[3, 3, 5, 4, 9]).1,
+            vec![(false, 1), (false, 1), (false, 2), (false, 1), (true, 0)]
+        );
+    }
+
+    #[test]
+    fn test_sightlines() {
+        let trees = vec![
+            vec![3, 0, 3, 7, 3],
+            vec![2, 5, 5, 1, 2],
+            vec![6, 5, 3, 3, 2],
+            vec![3, 3, 5, 4, 9],
+            vec![3, 5, 3, 9, 0],
+        ];
+        let sightlines = Sightlines::new(&trees);
+
+        assert_eq!(sightlines.distances[3][2], [2, 2, 1, 2]);
+        assert_eq!(sightlines.scenic_scores()[3][2], 8);
+        assert!(!sightlines.visible[1][3]);
+    }
+
+    #[test]
+    fn test_matches_directional_passes_on_random_forests() {
+        let mut rng = StdRng::seed_from_u64(2022);
+
+        for _ in 0..200 {
+            let rows_count = rng.gen_range(1..=30);
+            let columns_count = rng.gen_range(1..=30);
+            // Low trees make for many ties
+            let max_height = rng.gen_range(0..=9);
+            let trees: Vec<Vec<u8>> = (0..rows_count)
+                .map(|_| {
+                    (0..columns_count)
+                        .map(|_| rng.gen_range(0..=max_height))
+                        .collect()
+                })
+                .collect();
+
+            let sightlines = Sightlines::new(&trees);
+            assert_eq!(sightlines.visible, visible_trees_map(&trees), "{:?}", trees);
+            assert_eq!(
+                sightlines.scenic_scores(),
+                compute_scenic_scores(&trees),
+                "{:?}",
+                trees
+            );
+        }
+    }
+}
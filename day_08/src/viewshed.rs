@@ -0,0 +1,431 @@
+//! Trees visible from an arbitrary point, in or outside of the forest, along
+//! rays of any rational slope.
+
+use std::{collections::BTreeSet, ops::RangeInclusive, str::FromStr};
+
+use anyhow::{anyhow, Context};
+
+/// Step between consecutive grid points on a ray, always in lowest terms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Step {
+    pub rows: i64,
+    pub cols: i64,
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+impl Step {
+    fn new(rows: i64, cols: i64) -> Option<Self> {
+        // Unsigned, as `i64::MIN` has no positive counterpart
+        let divisor = i128::from(gcd(rows.unsigned_abs(), cols.unsigned_abs()));
+
+        (divisor != 0).then(|| Self {
+            rows: (i128::from(rows) / divisor) as i64,
+            cols: (i128::from(cols) / divisor) as i64,
+        })
+    }
+}
+
+impl FromStr for Step {
+    type Err = anyhow::Error;
+
+    /// Parses step given as `ROWS:COLS`, e.g. `-1:2` for one row up per two
+    /// columns to the right.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rows, cols) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("couldn't split '{}' at ':'", s))?;
+
+        Step::new(
+            rows.parse().context("couldn't parse rows of step")?,
+            cols.parse().context("couldn't parse columns of step")?,
+        )
+        .ok_or_else(|| anyhow!("step can't be 0:0"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rays {
+    /// Four cardinal and four diagonal directions
+    Compass,
+    /// Every direction passing through a tree
+    All,
+    Steps(Vec<Step>),
+}
+
+impl FromStr for Rays {
+    type Err = anyhow::Error;
+
+    /// Parses `compass`, `all`, or comma-separated steps, e.g. `1:2,-1:2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compass" => Ok(Self::Compass),
+            "all" => Ok(Self::All),
+            _ => Ok(Self::Steps(
+                s.split(',')
+                    .map(|step| step.parse())
+                    .collect::<Result<_, _>>()?,
+            )),
+        }
+    }
+}
+
+/// Observer position, which may lie outside of the forest, and height of its
+/// eyes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Observer {
+    pub row: i64,
+    pub col: i64,
+    pub height: i64,
+}
+
+impl Observer {
+    /// Places observer at `ROW,COL`, with eyes at the top of the tree standing
+    /// there, or at the ground level outside of the forest, unless `height`
+    /// is given.
+    pub fn new(
+        trees: &[Vec<u8>],
+        position: &str,
+        height: Option<i64>,
+    ) -> Result<Self, anyhow::Error> {
+        let (row, col) = position
+            .split_once(',')
+            .ok_or_else(|| anyhow!("couldn't split '{}' at ','", position))?;
+        let row: i64 = row.parse().context("couldn't parse observer row")?;
+        let col: i64 = col.parse().context("couldn't parse observer column")?;
+
+        let height = match height {
+            Some(height) => height,
+            None => tree_at(trees, row, col).map_or(0, i64::from),
+        };
+
+        Ok(Self { row, col, height })
+    }
+}
+
+fn tree_at(trees: &[Vec<u8>], row: i64, col: i64) -> Option<u8> {
+    let row = trees.get(usize::try_from(row).ok()?)?;
+
+    row.get(usize::try_from(col).ok()?).copied()
+}
+
+/// Numbers of steps from `start` along one axis which land within `0..len`,
+/// or `None` if the axis stays outside of it.
+fn distances_within(start: i64, step: i64, len: i64) -> Option<RangeInclusive<i128>> {
+    let (mut start, mut step, len) = (i128::from(start), i128::from(step), i128::from(len));
+
+    if step == 0 {
+        return (0..len).contains(&start).then_some(1..=i128::MAX);
+    }
+    // Walking backwards is walking forwards along the mirrored axis
+    if step < 0 {
+        (start, step) = (len - 1 - start, -step);
+    }
+
+    Some((step - 1 - start).div_euclid(step)..=(len - 1 - start).div_euclid(step))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Viewshed {
+    pub observer: Observer,
+    pub visible: Vec<Vec<bool>>,
+}
+
+impl Viewshed {
+    /// Finds trees visible from observer along given rays. Tree is visible
+    /// when the line from observer's eyes to its top passes above all trees
+    /// before it on the ray.
+    pub fn new(trees: &[Vec<u8>], observer: Observer, rays: &Rays) -> Self {
+        let rows_count = trees.len() as i64;
+        let columns_count = trees.first().map_or(0, |row| row.len()) as i64;
+
+        let steps: BTreeSet<Step> = match rays {
+            Rays::Compass => (-1..=1)
+                .flat_map(|rows| (-1..=1).filter_map(move |cols| Step::new(rows, cols)))
+                .collect(),
+            Rays::All => (0..rows_count)
+                .flat_map(|row| {
+                    (0..columns_count)
+                        .filter_map(move |col| Step::new(row - observer.row, col - observer.col))
+                })
+                .collect(),
+            Rays::Steps(steps) => steps.iter().copied().collect(),
+        };
+
+        let mut visible = vec![vec![false; columns_count as usize]; rows_count as usize];
+
+        for step in steps {
+            // Only walk the part of the ray within the forest, which may be
+            // none of it
+            let (Some(rows), Some(cols)) = (
+                distances_within(observer.row, step.rows, rows_count),
+                distances_within(observer.col, step.cols, columns_count),
+            ) else {
+                continue;
+            };
+            let first = 1.max(*rows.start()).max(*cols.start());
+            let last = (*rows.end()).min(*cols.end());
+
+            // Steepest slope towards a tree top so far, as height difference
+            // and number of steps, wide enough for any eyes height
+            let mut steepest: Option<(i128, i128)> = None;
+
+            for distance in first..=last {
+                let row = (i128::from(observer.row) + i128::from(step.rows) * distance) as usize;
+                let col = (i128::from(observer.col) + i128::from(step.cols) * distance) as usize;
+                let height = trees[row][col];
+
+                let rise = i128::from(height) - i128::from(observer.height);
+                if steepest.is_none_or(|(top, at)| rise * at > top * distance) {
+                    visible[row][col] = true;
+                    steepest = Some((rise, distance));
+                }
+            }
+        }
+
+        Self { observer, visible }
+    }
+
+    pub fn count(&self) -> usize {
+        self.visible.iter().flatten().filter(|&&v| v).count()
+    }
+
+    /// Draws forest with heights of visible trees, `.` in place of hidden ones,
+    /// and `@` at observer's position.
+    pub fn render(&self, trees: &[Vec<u8>]) -> String {
+        let mut out = String::new();
+
+        for (row, line) in trees.iter().enumerate() {
+            for (col, height) in line.iter().enumerate() {
+                let c = if (row as i64, col as i64) == (self.observer.row, self.observer.col) {
+                    '@'
+                } else if self.visible[row][col] {
+                    char::from(b'0' + height)
+                } else {
+                    '.'
+                };
+                out.push(c);
+            }
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn trees() -> Vec<Vec<u8>> {
+        vec![
+            vec![3, 0, 3, 7, 3],
+            vec![2, 5, 5, 1, 2],
+            vec![6, 5, 3, 3, 2],
+            vec![3, 3, 5, 4, 9],
+            vec![3, 5, 3, 9, 0],
+        ]
+    }
+
+    fn render(position: &str, height: Option<i64>, rays: &str) -> String {
+        let trees = trees();
+        let observer = Observer::new(&trees, position, height).unwrap();
+        let viewshed = Viewshed::new(&trees, observer, &rays.parse().unwrap());
+
+        viewshed.render(&trees)
+    }
+
+    #[test]
+    fn test_compass() {
+        // From the top of tree of height 5 in the middle of the 4th row.
+        // Further tree of the same height as the one before it is still
+        // visible, as it's seen at a shallower angle
+        assert_eq!(
+            render("3,2", None, "compass"),
+            "\
+.....
+..5.2
+.533.
+33@49
+.539.
+"
+        );
+    }
+
+    #[test]
+    fn test_observer_height() {
+        // Looking from high above reveals trees hidden from the top of the tree
+        assert_eq!(
+            render("3,2", Some(20), "compass"),
+            "\
+..3..
+2.5.2
+.533.
+33@49
+.539.
+"
+        );
+        // From the ground, tree hides the one of the same height behind it
+        assert_eq!(
+            render("3,2", Some(0), "0:1,0:-1"),
+            "\
+.....
+.....
+.....
+.3@49
+.....
+"
+        );
+    }
+
+    #[test]
+    fn test_outside_observer() {
+        // Looking east along the 2nd row from the ground, west of the forest
+        assert_eq!(
+            render("1,-2", None, "0:1"),
+            "\
+.....
+25...
+.....
+.....
+.....
+"
+        );
+    }
+
+    #[test]
+    fn test_outside_observer_diagonal() {
+        // Looking south-east from the ground, north-west of the forest, the
+        // ray crosses the forest diagonally and leaves it at the opposite
+        // corner
+        assert_eq!(
+            render("-2,-2", None, "1:1"),
+            "\
+3....
+.5...
+.....
+.....
+.....
+"
+        );
+
+        // From high above, every tree is seen at a shallower angle than the
+        // ones before it
+        let trees = trees();
+        let observer = Observer::new(&trees, "-1,2", Some(100)).unwrap();
+        assert_eq!(Viewshed::new(&trees, observer, &Rays::All).count(), 25);
+    }
+
+    #[test]
+    fn test_distant_observer() {
+        // Only the ray pointing north reaches the forest, and it's walked
+        // within the forest only, rather than all the way from the observer
+        let start = Instant::now();
+        assert_eq!(
+            render("3000000000,2", None, "compass"),
+            "\
+.....
+.....
+.....
+..5..
+..3..
+"
+        );
+        assert_eq!(
+            render("-2000000,-2000000", None, "1:1,1:2"),
+            "\
+3....
+.5...
+.....
+.....
+.....
+"
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_extreme_heights() {
+        // From far above, whole rays are visible
+        assert_eq!(
+            render("2,2", Some(i64::MAX), "compass"),
+            "\
+3.3.3
+.551.
+65@32
+.354.
+3.3.0
+"
+        );
+        // From far below, only the nearest trees are
+        assert_eq!(
+            render("2,2", Some(i64::MIN), "compass"),
+            "\
+.....
+.551.
+.5@3.
+.354.
+.....
+"
+        );
+    }
+
+    #[test]
+    fn test_slopes() {
+        // Slope 1:2 from the top-left corner passes (1, 2) and (2, 4), where
+        // the shorter tree is hidden behind the taller one
+        assert_eq!(
+            render("0,0", None, "1:2"),
+            "\
+@....
+..5..
+.....
+.....
+.....
+"
+        );
+    }
+
+    #[test]
+    fn test_all_rays() {
+        let trees = trees();
+        let observer = Observer::new(&trees, "2,2", Some(3)).unwrap();
+        let viewshed = Viewshed::new(&trees, observer, &Rays::All);
+
+        // Every neighbour is visible, and tree of height 9 at (4, 3) is seen
+        // along 2:1 although it's neither in a row, column nor diagonal
+        assert!((1..=3)
+            .all(|row| (1..=3).all(|col| viewshed.visible[row][col] || row == 2 && col == 2)));
+        assert!(viewshed.visible[4][3]);
+        assert!(viewshed.count() > 8);
+    }
+
+    #[test]
+    fn test_parse_rays() {
+        assert_eq!(
+            "2:4,-3:0".parse::<Rays>().unwrap(),
+            Rays::Steps(vec![Step { rows: 1, cols: 2 }, Step { rows: -1, cols: 0 }])
+        );
+        assert_eq!(
+            "-9223372036854775808:0,-9223372036854775808:3"
+                .parse::<Rays>()
+                .unwrap(),
+            Rays::Steps(vec![
+                Step { rows: -1, cols: 0 },
+                Step {
+                    rows: i64::MIN,
+                    cols: 3
+                }
+            ])
+        );
+        assert!("0:0".parse::<Rays>().is_err());
+        assert!("1/2".parse::<Rays>().is_err());
+    }
+}
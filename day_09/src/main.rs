@@ -1,7 +1,11 @@
 use std::{collections::HashSet, str::FromStr};
 
 use anyhow::{bail, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 #[derive(Clone, Debug, PartialEq)]
 enum Move {
@@ -31,9 +35,9 @@ impl FromStr for Move {
     }
 }
 
-impl Into<([i32; 2], usize)> for &Move {
-    fn into(self) -> ([i32; 2], usize) {
-        match self {
+impl From<&Move> for ([i32; 2], usize) {
+    fn from(val: &Move) -> Self {
+        match val {
             Move::Left(times) => ([-1, 0], *times),
             Move::Right(times) => ([1, 0], *times),
             Move::Up(times) => ([0, 1], *times),
@@ -118,11 +122,11 @@ fn main() -> Result<(), anyhow::Error> {
     let input_string = read_file_to_string(&input_file_path)?;
     let Problem { moves } = input_string.parse()?;
 
-    let visited_positions = simulate_rope::<2>(&moves);
+    let visited_positions = profile("part 1", || simulate_rope::<2>(&moves));
 
     println!("Part 1 solution: {}", visited_positions.len());
 
-    let visited_positions = simulate_rope::<10>(&moves);
+    let visited_positions = profile("part 2", || simulate_rope::<10>(&moves));
 
     println!("Part 2 solution: {}", visited_positions.len());
 
@@ -1,7 +1,11 @@
 use std::str::FromStr;
 
 use anyhow::{anyhow, bail, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 #[derive(Debug)]
 enum Instruction {
@@ -111,12 +115,15 @@ fn main() -> Result<(), anyhow::Error> {
     let input_string = read_file_to_string(&input_file_path)?;
     let Problem { instructions } = input_string.parse()?;
 
-    let register_history = execute(&instructions);
+    let register_history = profile("execution", || execute(&instructions));
 
-    println!("Part 1 solution: {}", calculate_score(&register_history));
+    println!(
+        "Part 1 solution: {}",
+        profile("part 1", || calculate_score(&register_history))
+    );
     println!(
         "Part 2 solution:\n{}",
-        get_screen_output(&register_history, 40, 6).trim_end()
+        profile("part 2", || get_screen_output(&register_history, 40, 6)).trim_end()
     );
 
     Ok(())
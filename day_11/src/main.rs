@@ -1,7 +1,11 @@
 use std::{collections::VecDeque, str::FromStr};
 
 use anyhow::{anyhow, bail, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 #[derive(Clone, Debug)]
 enum Either<L, R> {
@@ -97,7 +101,7 @@ struct DivisibilityTest {
 
 impl DivisibilityTest {
     fn check(&self, worry_level: u64) -> usize {
-        if worry_level % self.operand == 0 {
+        if worry_level.is_multiple_of(self.operand) {
             self.if_true_receiver
         } else {
             self.if_false_receiver
@@ -280,14 +284,18 @@ fn main() -> Result<(), anyhow::Error> {
     let input_string = read_file_to_string(&input_file_path)?;
 
     let Problem { monkeys } = input_string.parse()?;
-    let inspected_items = do_your_business(&mut monkeys.clone(), 20, true);
+    let inspected_items = profile("part 1", || {
+        do_your_business(&mut monkeys.clone(), 20, true)
+    });
 
     println!(
         "Part 1 solution: {}",
         score_monkey_business(&inspected_items)
     );
 
-    let inspected_items = do_your_business(&mut monkeys.clone(), 10000, false);
+    let inspected_items = profile("part 2", || {
+        do_your_business(&mut monkeys.clone(), 10000, false)
+    });
     println!(
         "Part 2 solution: {}",
         score_monkey_business(&inspected_items)
@@ -343,8 +351,8 @@ Monkey 3:
 
         assert_eq!(monkeys[0].items, vec![10, 12, 14, 26, 34]);
         assert_eq!(monkeys[1].items, vec![245, 93, 53, 199, 115]);
-        assert_eq!(monkeys[2].items, vec![]);
-        assert_eq!(monkeys[3].items, vec![]);
+        assert_eq!(monkeys[2].items, Vec::<u64>::new());
+        assert_eq!(monkeys[3].items, Vec::<u64>::new());
     }
 
     #[test]
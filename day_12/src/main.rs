@@ -1,10 +1,14 @@
 use std::{collections::VecDeque, str::FromStr};
 
 use anyhow::{anyhow, bail, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
 
-const START: u8 = 'a' as u8 - 1;
-const END: u8 = 'z' as u8 + 1;
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
+
+const START: u8 = b'a' - 1;
+const END: u8 = b'z' + 1;
 
 #[derive(Debug)]
 struct Problem {
@@ -19,11 +23,11 @@ impl FromStr for Problem {
 
         // Turn 'S' and 'E' into respectively numbers one lower/higher than
         // 'a'/'z' (lowest and highest points on the map)
-        let start = find_named_point(&map, 'S' as u8)
+        let start = find_named_point(&map, b'S')
             .ok_or_else(|| anyhow!("couldn't find starting point coordinates"))?;
         map[start.0][start.1] = START;
 
-        let end = find_named_point(&map, 'E' as u8)
+        let end = find_named_point(&map, b'E')
             .ok_or_else(|| anyhow!("couldn't find end point coordinates"))?;
         map[end.0][end.1] = END;
 
@@ -32,10 +36,9 @@ impl FromStr for Problem {
 }
 
 fn find_named_point(map: &[Vec<u8>], point: u8) -> Option<(usize, usize)> {
-    for x in 0..map.len() {
-        // Assumes every row has the same length
-        for y in 0..map[0].len() {
-            if map[x][y] == point {
+    for (x, row) in map.iter().enumerate() {
+        for (y, &height) in row.iter().enumerate() {
+            if height == point {
                 return Some((x, y));
             }
         }
@@ -72,7 +75,7 @@ fn find_shortest_path(
     start_point_label: u8,
     end_point_label: u8,
 ) -> Result<u32, anyhow::Error> {
-    let end_coords = find_named_point(&map, end_point_label)
+    let end_coords = find_named_point(map, end_point_label)
         .ok_or_else(|| anyhow!("couldn't find ending point coordinates"))?;
 
     let x_dim = map.len();
@@ -101,7 +104,7 @@ fn find_shortest_path(
             // We can access point `p` from points not lower than `p_height - 1`
             // (so also from the ones that are higher than `p`)
             .filter(|(i_x, i_y)| {
-                map[*i_x][*i_y] >= p_height - 1 && path_lengths[*i_x][*i_y] == None
+                map[*i_x][*i_y] >= p_height - 1 && path_lengths[*i_x][*i_y].is_none()
             })
             .collect::<Vec<_>>();
 
@@ -120,10 +123,10 @@ fn main() -> Result<(), anyhow::Error> {
     let input_string = read_file_to_string(&input_file_path)?;
     let Problem { map } = input_string.parse()?;
 
-    let shortest_path = find_shortest_path(&map, START, END)?;
+    let shortest_path = profile("part 1", || find_shortest_path(&map, START, END))?;
     println!("Part 1 solution: {}", shortest_path);
 
-    let shortest_path = find_shortest_path(&map, 'a' as u8, END)?;
+    let shortest_path = profile("part 2", || find_shortest_path(&map, b'a', END))?;
     println!("Part 2 solution: {}", shortest_path);
 
     Ok(())
@@ -170,7 +173,7 @@ abdefghi";
     #[test]
     fn test_find_shortest_path_from_elevation() {
         let Problem { map } = TEST_INPUT.parse().unwrap();
-        let shortest_path = find_shortest_path(&map, 'a' as u8, END).unwrap();
+        let shortest_path = find_shortest_path(&map, b'a', END).unwrap();
 
         assert_eq!(shortest_path, 29);
     }
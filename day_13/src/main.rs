@@ -1,7 +1,11 @@
 use std::{cmp::Ordering, str::FromStr};
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 #[derive(Clone, Debug, PartialEq, Eq, Ord)]
 enum Packet<T>
@@ -77,6 +81,7 @@ impl FromStr for Packet<u8> {
     }
 }
 
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for Packet<u8> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         use Packet::*;
@@ -129,10 +134,10 @@ fn find_indices_of_packets_in_correct_order(packet_pairs: &[(Packet<u8>, Packet<
         .collect()
 }
 
-fn find_decoder_key(packet_pairs: &Vec<(Packet<u8>, Packet<u8>)>) -> Result<u64, anyhow::Error> {
+fn find_decoder_key(packet_pairs: &[(Packet<u8>, Packet<u8>)]) -> Result<u64, anyhow::Error> {
     let mut packets = packet_pairs
-        .clone()
-        .into_iter()
+        .iter()
+        .cloned()
         .flat_map(|pair| [pair.0, pair.1])
         .collect::<Vec<_>>();
 
@@ -162,14 +167,19 @@ fn main() -> Result<(), anyhow::Error> {
 
     let Problem { packet_pairs } = input_string.parse()?;
 
-    let indices_in_correct_order = find_indices_of_packets_in_correct_order(&packet_pairs);
+    let indices_in_correct_order = profile("part 1", || {
+        find_indices_of_packets_in_correct_order(&packet_pairs)
+    });
 
     println!(
         "Part 1 solution: {}",
         indices_in_correct_order.into_iter().sum::<u64>()
     );
 
-    println!("Part 2 solution: {}", find_decoder_key(&packet_pairs)?);
+    println!(
+        "Part 2 solution: {}",
+        profile("part 2", || find_decoder_key(&packet_pairs))?
+    );
 
     Ok(())
 }
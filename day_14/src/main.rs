@@ -1,7 +1,11 @@
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 const EMPTY: char = '.';
 const ROCK: char = '#';
@@ -96,17 +100,17 @@ impl FromStr for Problem {
     }
 }
 
-fn print_map(map: &Vec<Vec<char>>) {
+fn print_map(map: &[Vec<char>]) {
     for y in 0..map[0].len() {
-        for x in 0..map.len() {
-            print!("{}", map[x][y]);
+        for column in map {
+            print!("{}", column[y]);
         }
         println!();
     }
 }
 
-fn simulate_sand(map: &Vec<Vec<char>>, sand_source: (usize, usize)) -> (Vec<Vec<char>>, u64) {
-    let mut map = map.clone();
+fn simulate_sand(map: &[Vec<char>], sand_source: (usize, usize)) -> (Vec<Vec<char>>, u64) {
+    let mut map = map.to_vec();
     let mut sand_units_that_came_to_rest = 0;
     let x_max = map.len() - 1;
     let y_max = map[0].len() - 1;
@@ -161,25 +165,25 @@ fn simulate_sand(map: &Vec<Vec<char>>, sand_source: (usize, usize)) -> (Vec<Vec<
     (map, sand_units_that_came_to_rest)
 }
 
-fn add_floor(map: &Vec<Vec<char>>) -> Vec<Vec<char>> {
-    let mut map = map.clone();
+fn add_floor(map: &[Vec<char>]) -> Vec<Vec<char>> {
+    let mut map = map.to_vec();
 
-    for x in 0..map.len() {
-        map[x].push(EMPTY);
-        map[x].push(ROCK);
+    for column in map.iter_mut() {
+        column.push(EMPTY);
+        column.push(ROCK);
     }
 
     map
 }
 
 fn simulate_sand_with_endless_floor(
-    map: &Vec<Vec<char>>,
+    map: &[Vec<char>],
     sand_source: (usize, usize),
 ) -> (Vec<Vec<char>>, u64) {
     // Add padding columns to allow for sand to fill gaps at edges of map
     let y_len = map[0].len();
     let mut new_map = vec![vec![EMPTY; y_len]];
-    new_map.append(&mut map.clone());
+    new_map.extend_from_slice(map);
     new_map.push(vec![EMPTY; y_len]);
 
     // Adjust sand source position, moved due to added padding columns
@@ -205,7 +209,7 @@ fn simulate_sand_with_endless_floor(
             if sand_x == 0 {
                 // Could we possibly move right?
                 // TODO: could we avoid this duplication?
-                if sand_x + 1 <= x_max && map[sand_x + 1][sand_y + 1] == EMPTY {
+                if sand_x < x_max && map[sand_x + 1][sand_y + 1] == EMPTY {
                     sand_x += 1;
                     sand_y += 1;
                     continue 'sand;
@@ -272,11 +276,13 @@ fn main() -> Result<(), anyhow::Error> {
 
     println!("-----------------------------------------------------------------------");
 
-    let (filled_map, pt1_sand_count) = simulate_sand(&map, sand_source);
+    let (filled_map, pt1_sand_count) = profile("part 1", || simulate_sand(&map, sand_source));
 
     print_map(&filled_map);
 
-    let (filled_map, pt2_sand_count) = simulate_sand_with_endless_floor(&map, sand_source);
+    let (filled_map, pt2_sand_count) = profile("part 2", || {
+        simulate_sand_with_endless_floor(&map, sand_source)
+    });
 
     print_map(&filled_map);
 
@@ -1,7 +1,16 @@
 use std::{collections::HashSet, str::FromStr};
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+use common::{
+    get_arg,
+    profiling::profile,
+    progress::{cancel_on_ctrl_c, get_budget, Outcome, Progress},
+    read_file_to_string,
+};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 // TODO: collect empty ranges - spreading out from sensor position to +/-
 // closest beacon positions (in both coordinates). For part 1: find all these
@@ -109,7 +118,7 @@ fn merge_ranges(mut ranges: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
     merged_ranges
 }
 
-fn get_covered_ranges_at_row(y: i32, reports: &Vec<(Sensor, Beacon)>) -> Vec<(i32, i32)> {
+fn get_covered_ranges_at_row(y: i32, reports: &[(Sensor, Beacon)]) -> Vec<(i32, i32)> {
     let coverages_at_y = reports
         .iter()
         .filter_map(|(sensor, beacon)| find_sensor_coverage_at_row(y, sensor, beacon))
@@ -118,7 +127,7 @@ fn get_covered_ranges_at_row(y: i32, reports: &Vec<(Sensor, Beacon)>) -> Vec<(i3
     merge_ranges(coverages_at_y)
 }
 
-fn find_coverage_for_row(y: i32, reports: &Vec<(Sensor, Beacon)>) -> u32 {
+fn find_coverage_for_row(y: i32, reports: &[(Sensor, Beacon)]) -> u32 {
     let beacons_at_row = reports
         .iter()
         .filter(|(_, beacon)| beacon.1 == y)
@@ -139,47 +148,64 @@ fn find_coverage_for_row(y: i32, reports: &Vec<(Sensor, Beacon)>) -> u32 {
             }
         })
         .collect::<HashSet<_>>()
-        .into_iter()
-        .count() as u32;
+        .len() as u32;
 
     covered_cells - beacons_at_row
 }
 
+/// Scans rows from `range_start` to `range_end` looking for the only one with
+/// a gap in coverage, advancing `progress` by one step per row. When `progress`
+/// asks to stop before the row is found, partial `None` is returned.
 fn find_distress_beacons_signal(
     range_start: i32,
     range_end: i32,
-    reports: &Vec<(Sensor, Beacon)>,
-) -> Option<u64> {
+    reports: &[(Sensor, Beacon)],
+    progress: &Progress,
+) -> Outcome<Option<u64>> {
     for y in range_start..=range_end {
+        if progress.should_stop() {
+            return progress.finish(None);
+        }
+
         let ranges = get_covered_ranges_at_row(y, reports);
         if ranges.len() == 2 {
-            return Some(4000000 * (ranges[0].1 as u64 + 1) + y as u64);
+            return progress.finish(Some(4000000 * (ranges[0].1 as u64 + 1) + y as u64));
         }
+
+        progress.advance(1);
     }
 
-    None
+    progress.finish(None)
 }
 
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
     let input_string = read_file_to_string(&input_file_path)?;
+    let budget = get_budget()?;
+    cancel_on_ctrl_c()?;
 
     let Problem { reports } = input_string.parse()?;
 
     println!(
         "Part 1 solution: {}",
-        find_coverage_for_row(2000000, &reports)
-    );
-    println!(
-        "Part 2 solution: {}",
-        find_distress_beacons_signal(0, 4000000, &reports).unwrap()
+        profile("part 1", || find_coverage_for_row(2000000, &reports))
     );
 
+    let progress = Progress::new("part 2", 4000000 + 1).with_budget(budget);
+    let signal = profile("part 2", || {
+        find_distress_beacons_signal(0, 4000000, &reports, &progress)
+    })
+    .map(|signal| signal.map_or_else(|| "not found".to_string(), |s| s.to_string()));
+
+    println!("Part 2 solution: {}", signal);
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     const TEST_INPUT: &str = "\
@@ -233,8 +259,17 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
     #[test]
     fn test_find_distress_beacons_signal() {
         let Problem { reports } = TEST_INPUT.parse().unwrap();
-        let signal = find_distress_beacons_signal(0, 20, &reports);
+        let signal = find_distress_beacons_signal(0, 20, &reports, &Progress::hidden(21));
+
+        assert_eq!(signal, Outcome::Complete(Some(56000011)));
+    }
+
+    #[test]
+    fn test_find_distress_beacons_signal_out_of_budget() {
+        let Problem { reports } = TEST_INPUT.parse().unwrap();
+        let progress = Progress::hidden(21).with_budget(Some(Duration::ZERO));
+        let signal = find_distress_beacons_signal(0, 20, &reports, &progress);
 
-        assert_eq!(signal, Some(56000011));
+        assert_eq!(signal, Outcome::Partial(None));
     }
 }
@@ -4,15 +4,28 @@ use std::{
 };
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+#[cfg(not(feature = "serde"))]
+use common::read_problem;
+#[cfg(feature = "serde")]
+use common::serialization::read_problem;
+use common::{
+    get_arg,
+    profiling::profile,
+    progress::{cancel_on_ctrl_c, get_budget, Progress},
+};
 use itertools::Itertools;
 use rand::{seq::SliceRandom, Rng};
 use rayon::prelude::*;
 
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
+
 // TODO: Priority queue scored by time_left * flow_rate - distance_to_valve - 1
 // (time to turn valve)
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Problem {
     label_to_idx: HashMap<String, usize>,
     adjacency_lists: Vec<Vec<usize>>,
@@ -85,17 +98,17 @@ impl FromStr for Problem {
     }
 }
 
-fn compute_shortest_paths(adjacency_lists: &Vec<Vec<usize>>) -> Vec<Vec<Option<u32>>> {
+fn compute_shortest_paths(adjacency_lists: &[Vec<usize>]) -> Vec<Vec<Option<u32>>> {
     let number_of_nodes = adjacency_lists.len();
     let mut path_lengths = vec![vec![None; number_of_nodes]; number_of_nodes];
 
-    for source_node in 0..number_of_nodes {
+    for (source_node, source_path_lengths) in path_lengths.iter_mut().enumerate() {
         let mut q = VecDeque::from([(source_node, 0u32)]);
         while let Some((node, path_len)) = q.pop_front() {
-            path_lengths[source_node][node] = Some(path_len);
+            source_path_lengths[node] = Some(path_len);
 
             for &adjacent_node in &adjacency_lists[node] {
-                if path_lengths[source_node][adjacent_node] == None {
+                if source_path_lengths[adjacent_node].is_none() {
                     q.push_back((adjacent_node, path_len + 1));
                 }
             }
@@ -105,11 +118,8 @@ fn compute_shortest_paths(adjacency_lists: &Vec<Vec<usize>>) -> Vec<Vec<Option<u
     path_lengths
 }
 
-fn bruteforce(
-    shortest_paths: &Vec<Vec<Option<u32>>>,
-    flow_rates: &Vec<u32>,
-    start_node: usize,
-) -> u32 {
+#[allow(dead_code)]
+fn bruteforce(shortest_paths: &[Vec<Option<u32>>], flow_rates: &[u32], start_node: usize) -> u32 {
     let number_of_nodes = shortest_paths.len();
     let unopened_valves: HashSet<usize> =
         HashSet::from_iter((0..number_of_nodes).filter(|&node| flow_rates[node] > 0));
@@ -145,8 +155,8 @@ fn bruteforce(
 }
 
 fn find_optimal_moves(
-    shortest_paths: &Vec<Vec<Option<u32>>>,
-    flow_rates: &Vec<u32>,
+    shortest_paths: &[Vec<Option<u32>>],
+    flow_rates: &[u32],
     start_node: usize,
     valves: &HashSet<usize>,
     time_left: u32,
@@ -164,9 +174,7 @@ fn find_optimal_moves(
             .iter()
             .filter_map(|&node| {
                 let flow_rate = flow_rates[node];
-                let Some(distance) = shortest_paths[current_node][node] else {
-                    return None;
-                };
+                let distance = shortest_paths[current_node][node]?;
 
                 // Compute pressure flow lost on every unopened valve for distance + 1 minutes
                 let flow_lost_on_every_other_unopened_valve =
@@ -203,10 +211,14 @@ fn find_optimal_moves(
     pressure_released
 }
 
+/// Tries splitting valves between two actors at every possible point, advancing
+/// `progress` by one step per split point. Stops early, returning best result
+/// so far, when `progress` asks to.
 fn part_2(
-    shortest_paths: &Vec<Vec<Option<u32>>>,
-    flow_rates: &Vec<u32>,
+    shortest_paths: &[Vec<Option<u32>>],
+    flow_rates: &[u32],
     label_to_idx: &HashMap<String, usize>,
+    progress: &Progress,
 ) -> u32 {
     let mut rng = rand::thread_rng();
     let mut valves = (0..shortest_paths.len())
@@ -218,20 +230,24 @@ fn part_2(
     let mut best = 0;
 
     for s in 1..valves.len() {
+        if progress.should_stop() {
+            break;
+        }
+
         let valves = valves.clone();
         let (left, right) = valves.split_at(s);
 
         for _ in 0..100 {
             let pressure_released_1 = find_optimal_moves(
-                &shortest_paths,
-                &flow_rates,
+                shortest_paths,
+                flow_rates,
                 label_to_idx["AA"],
                 &HashSet::from_iter(left.iter().cloned()),
                 26,
             );
             let pressure_released_2 = find_optimal_moves(
-                &shortest_paths,
-                &flow_rates,
+                shortest_paths,
+                flow_rates,
                 label_to_idx["AA"],
                 &HashSet::from_iter(right.iter().cloned()),
                 26,
@@ -243,6 +259,8 @@ fn part_2(
                 best = pressure_released;
             }
         }
+
+        progress.advance(1);
     }
 
     best
@@ -250,46 +268,56 @@ fn part_2(
 
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
-    let input_string = read_file_to_string(&input_file_path)?;
-    let p: Problem = input_string.parse()?;
-
+    let p: Problem = read_problem(&input_file_path)?;
+    let budget = get_budget()?;
+    cancel_on_ctrl_c()?;
+
+    #[cfg(feature = "serde")]
+    let path_lengths = common::serialization::cached("shortest-paths", || {
+        compute_shortest_paths(&p.adjacency_lists)
+    })?;
+    #[cfg(not(feature = "serde"))]
     let path_lengths = compute_shortest_paths(&p.adjacency_lists);
     let valves = HashSet::from_iter((0..path_lengths.len()).filter(|&node| p.flow_rates[node] > 0));
 
-    let mut best = 0;
+    let best = profile("part 1", || {
+        let mut best = 0;
 
-    // Run a couple of times
-    for _ in 0..10000 {
-        let result = find_optimal_moves(
-            &path_lengths,
-            &p.flow_rates,
-            p.label_to_idx["AA"],
-            &valves,
-            30,
-        );
-        if result > best {
-            best = result;
+        // Run a couple of times
+        for _ in 0..10000 {
+            let result = find_optimal_moves(
+                &path_lengths,
+                &p.flow_rates,
+                p.label_to_idx["AA"],
+                &valves,
+                30,
+            );
+            if result > best {
+                best = result;
+            }
         }
-    }
 
-    println!("Part 1 solution: {}", best);
+        best
+    });
 
-    for i in 0..1 {
-        let r = (0..100)
-            .into_par_iter()
-            .map(|_| part_2(&path_lengths, &p.flow_rates, &p.label_to_idx))
-            .max();
-
-        let r = r.unwrap();
-        if r > best {
-            best = r;
-            println!("new best: {}", best);
-        }
+    println!("Part 1 solution: {}", best);
 
-        println!("done {} iterations", i * 1000);
-    }
+    let runs = 100;
+    let progress =
+        Progress::new("part 2", runs * (valves.len() as u64).saturating_sub(1)).with_budget(budget);
 
-    println!("Part 2 solution: {}", best);
+    let part_2_best = profile("part 2", || {
+        (0..runs)
+            .into_par_iter()
+            .map(|_| part_2(&path_lengths, &p.flow_rates, &p.label_to_idx, &progress))
+            .max()
+            .unwrap()
+    });
+
+    println!(
+        "Part 2 solution: {}",
+        progress.finish(best.max(part_2_best))
+    );
 
     Ok(())
 }
@@ -1,5 +1,9 @@
 use anyhow::Context;
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 fn get_shapes() -> Vec<(usize, Vec<(usize, usize)>)> {
     let plank_parts = vec![
@@ -22,11 +26,12 @@ fn get_shapes() -> Vec<(usize, Vec<(usize, usize)>)> {
     ]
 }
 
-fn print_board(columns: &Vec<Vec<bool>>) {
+#[allow(dead_code)]
+fn print_board(columns: &[Vec<bool>]) {
     for row in (0..columns[0].len()).rev() {
         print!("{row:6} |");
-        for col in 0..columns.len() {
-            if columns[col][row] {
+        for column in columns {
+            if column[row] {
                 print!("#");
             } else {
                 print!(".");
@@ -53,12 +58,12 @@ fn simulate_tetris(instructions: &str, rocks_to_drop: usize, cycle_start: usize,
     // let cycle_len = 53;
 
     for (shape_height, rock_parts) in get_shapes().iter().cycle() {
-        for c in 0..columns.len() {
-            columns[c].resize(top_of_highest_block + 3 + shape_height, false);
+        for column in columns.iter_mut() {
+            column.resize(top_of_highest_block + 3 + shape_height, false);
         }
 
         let mut rock_parts = rock_parts
-            .into_iter()
+            .iter()
             .map(|&(col, row)| (col, row + top_of_highest_block + 3))
             .collect::<Vec<_>>();
 
@@ -70,9 +75,7 @@ fn simulate_tetris(instructions: &str, rocks_to_drop: usize, cycle_start: usize,
                         // left
                     } else {
                         // Check if cell to left of every piece is unoccupied
-                        let all_free = rock_parts
-                            .iter()
-                            .all(|&(col, row)| columns[col - 1][row] == false);
+                        let all_free = rock_parts.iter().all(|&(col, row)| !columns[col - 1][row]);
 
                         if all_free {
                             // Move piece
@@ -86,9 +89,7 @@ fn simulate_tetris(instructions: &str, rocks_to_drop: usize, cycle_start: usize,
                     if rock_parts[rock_parts.len() - 1].0 == 6 {
                         // Shape touches rightmost column
                     } else {
-                        let all_free = rock_parts
-                            .iter()
-                            .all(|&(col, row)| columns[col + 1][row] == false);
+                        let all_free = rock_parts.iter().all(|&(col, row)| !columns[col + 1][row]);
 
                         if all_free {
                             // Move piece
@@ -107,7 +108,7 @@ fn simulate_tetris(instructions: &str, rocks_to_drop: usize, cycle_start: usize,
             // Move down
             let all_free = rock_parts
                 .iter()
-                .all(|&(col, row)| row > 0 && columns[col][row - 1] == false);
+                .all(|&(col, row)| row > 0 && !columns[col][row - 1]);
 
             if all_free {
                 for (_, ref mut row) in rock_parts.iter_mut() {
@@ -188,7 +189,8 @@ fn simulate_tetris(instructions: &str, rocks_to_drop: usize, cycle_start: usize,
     (top_of_highest_block + height_from_cycle, columns)
 }
 
-fn detect_cycle(columns: &Vec<Vec<bool>>) -> Option<(usize, usize)> {
+#[allow(dead_code)]
+fn detect_cycle(columns: &[Vec<bool>]) -> Option<(usize, usize)> {
     let cols = columns.len();
     let rows = columns[0].len();
     let mut i = 0;
@@ -226,7 +228,9 @@ fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
     let input_string = read_file_to_string(&input_file_path)?;
     let instructions = input_string.trim();
-    let (height, columns) = simulate_tetris(&instructions, 1000000000000, 495, 2634);
+    let (height, _columns) = profile("part 2", || {
+        simulate_tetris(instructions, 1000000000000, 495, 2634)
+    });
 
     println!("Part 2 solution: {}", height);
 
@@ -1,7 +1,11 @@
 use std::{collections::VecDeque, str::FromStr};
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 // Construct a Boolean grid and add cubes one by one, checking if it has
 // neighbors (in canonical directions) - any neighbor means -2 from visible
@@ -26,7 +30,7 @@ impl FromStr for Problem {
                     .split(',')
                     // Shift coordinates, so we don't need to mind if we are
                     // looking for neighbors of box originally at 0-th index.
-                    .map(|n| n.parse::<usize>().and_then(|n| Ok(n + 2)))
+                    .map(|n| n.parse::<usize>().map(|n| n + 2))
                     .collect::<Result<Vec<_>, _>>()
                     .with_context(|| format!("parsing '{}'", line))?
                     .try_into()
@@ -72,7 +76,7 @@ fn get_surface_area(boxes: &Vec<[usize; 3]>) -> (usize, Vec<Vec<Vec<bool>>>) {
     (surface_area as usize, grid)
 }
 
-fn flood_count(grid: &Vec<Vec<Vec<bool>>>) -> usize {
+fn flood_count(grid: &[Vec<Vec<bool>>]) -> usize {
     let mut scheduled = vec![vec![vec![false; grid[0][0].len()]; grid[0].len()]; grid.len()];
     let mut hits = 0;
 
@@ -103,10 +107,13 @@ fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
     let input_string = read_file_to_string(&input_file_path)?;
     let Problem { boxes } = input_string.parse()?;
-    let (surface_area, grid) = get_surface_area(&boxes);
+    let (surface_area, grid) = profile("part 1", || get_surface_area(&boxes));
 
     println!("Part 1 solution: {}", surface_area);
-    println!("Part 2 solution: {}", flood_count(&grid));
+    println!(
+        "Part 2 solution: {}",
+        profile("part 2", || flood_count(&grid))
+    );
 
     Ok(())
 }
@@ -7,7 +7,21 @@ use regex::Regex;
 use std::str::FromStr;
 
 use anyhow::{ensure, Context};
-use common::{get_arg, read_file_to_string};
+use common::{
+    get_arg,
+    profiling::profile,
+    progress::{cancel_on_ctrl_c, get_budget, Outcome, Progress},
+    read_file_to_string,
+};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
+
+/// Number of simulations run between polls of [`Progress`]
+const PROGRESS_BATCH: u64 = 10_000;
+const QUALITY_LEVEL_SIMULATIONS: u64 = 1_000_000;
+const MAX_GEODES_SIMULATIONS: u64 = 10_000_000;
 
 #[derive(Debug, PartialEq, Eq)]
 enum Action {
@@ -236,7 +250,7 @@ fn run_randomized_simulation(blueprint: &Blueprint, time_left: u8) -> u64 {
     let mut simulation = Simulation::default();
 
     for _ in 0..time_left {
-        let actions = get_available_actions(&blueprint, &simulation);
+        let actions = get_available_actions(blueprint, &simulation);
         let action = actions
             .choose_weighted(&mut rng, |action| match action {
                 // Bias toward buying
@@ -247,30 +261,36 @@ fn run_randomized_simulation(blueprint: &Blueprint, time_left: u8) -> u64 {
                 BuyGeodeRobot => 5,
             })
             .unwrap();
-        simulation.tick_mut(&blueprint, &action);
+        simulation.tick_mut(blueprint, action);
     }
 
     simulation.geode_count
 }
 
-fn get_blueprint_quality_level(blueprint: &Blueprint) -> u64 {
-    let mut max_goedes = 0;
-
-    for _ in 0..1_000_000 {
-        let geodes = run_randomized_simulation(&blueprint, 24);
-        if geodes > max_goedes {
-            max_goedes = geodes;
-        }
-    }
-
-    blueprint.id * max_goedes
+fn get_blueprint_quality_level(blueprint: &Blueprint, progress: &Progress) -> u64 {
+    blueprint.id * get_blueprint_max_geodes(blueprint, 24, QUALITY_LEVEL_SIMULATIONS, progress)
 }
 
-fn get_blueprint_max_geodes(blueprint: &Blueprint, time_left: u8) -> u64 {
+/// Runs `simulations_count` randomized simulations, returning best geode count
+/// among them. Advances `progress` by one step per simulation, and stops early
+/// when it asks to.
+fn get_blueprint_max_geodes(
+    blueprint: &Blueprint,
+    time_left: u8,
+    simulations_count: u64,
+    progress: &Progress,
+) -> u64 {
     let mut max_goedes = 0;
 
-    for _ in 0..10_000_000 {
-        let geodes = run_randomized_simulation(&blueprint, time_left);
+    for i in 0..simulations_count {
+        if i % PROGRESS_BATCH == 0 {
+            if progress.should_stop() {
+                break;
+            }
+            progress.advance(PROGRESS_BATCH.min(simulations_count - i));
+        }
+
+        let geodes = run_randomized_simulation(blueprint, time_left);
         if geodes > max_goedes {
             max_goedes = geodes;
         }
@@ -279,35 +299,47 @@ fn get_blueprint_max_geodes(blueprint: &Blueprint, time_left: u8) -> u64 {
     max_goedes
 }
 
-fn part_1(blueprints: &Vec<Blueprint>) -> u64 {
-    blueprints
+fn part_1(blueprints: &[Blueprint], progress: &Progress) -> Outcome<u64> {
+    let quality_levels_sum = blueprints
         .par_iter()
-        .map(|blueprint| {
-            println!("Starting blueprint id: {}", blueprint.id);
-            get_blueprint_quality_level(blueprint)
-        })
-        .sum()
+        .map(|blueprint| get_blueprint_quality_level(blueprint, progress))
+        .sum();
+
+    progress.finish(quality_levels_sum)
 }
 
-fn part_2(blueprints: &Vec<Blueprint>) -> u64 {
-    blueprints
+fn part_2(blueprints: &[Blueprint], progress: &Progress) -> Outcome<u64> {
+    let max_geodes_product = blueprints
         .par_iter()
-        .map(|blueprint| {
-            println!("Starting blueprint id: {}", blueprint.id);
-            get_blueprint_max_geodes(&blueprint, 32)
-        })
-        .product()
+        .map(|blueprint| get_blueprint_max_geodes(blueprint, 32, MAX_GEODES_SIMULATIONS, progress))
+        .product();
+
+    progress.finish(max_geodes_product)
 }
 
 fn main() -> Result<(), anyhow::Error> {
     let input_file_path = get_arg(1).context("pass path to input file as first argument")?;
     let input_string = read_file_to_string(&input_file_path)?;
     let blueprints = parse_blueprints(&input_string)?;
+    let budget = get_budget()?;
+    cancel_on_ctrl_c()?;
+
+    let progress = Progress::new(
+        "part 1",
+        blueprints.len() as u64 * QUALITY_LEVEL_SIMULATIONS,
+    )
+    .with_budget(budget);
+    println!(
+        "Part 1 solution: {}",
+        profile("part 1", || part_1(&blueprints, &progress))
+    );
 
-    println!("Part 1 solution: {}", part_1(&blueprints));
+    let blueprints = &blueprints[..blueprints.len().min(3)];
+    let progress = Progress::new("part 2", blueprints.len() as u64 * MAX_GEODES_SIMULATIONS)
+        .with_budget(budget);
     println!(
         "Part 2 solution: {}",
-        part_2(&blueprints.into_iter().take(3).collect::<Vec<_>>())
+        profile("part 2", || part_2(blueprints, &progress))
     );
 
     Ok(())
@@ -344,6 +376,15 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         assert_eq!(actions, vec![Action::Noop]);
     }
 
+    #[test]
+    fn test_part_1_out_of_budget() {
+        let blueprints = parse_blueprints(TEST_INPUT).unwrap();
+        let progress = Progress::hidden(2 * QUALITY_LEVEL_SIMULATIONS)
+            .with_budget(Some(std::time::Duration::ZERO));
+
+        assert_eq!(part_1(&blueprints, &progress), Outcome::Partial(0));
+    }
+
     // #[test]
     // fn test_get_blueprint_quality_level() {
     //     let blueprints = parse_blueprints(TEST_INPUT).unwrap();
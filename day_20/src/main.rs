@@ -1,7 +1,11 @@
 use std::collections::VecDeque;
 
 use anyhow::{anyhow, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 fn parse_input(s: &str) -> Result<Vec<i64>, anyhow::Error> {
     s.lines()
@@ -12,10 +16,10 @@ fn parse_input(s: &str) -> Result<Vec<i64>, anyhow::Error> {
         .collect()
 }
 
-fn mix_numbers(ns: &Vec<i64>) -> Vec<i64> {
+fn mix_numbers(ns: &[i64]) -> Vec<i64> {
     let len = ns.len();
 
-    let mut q = ns.into_iter().map(|&n| (n, false)).collect::<VecDeque<_>>();
+    let mut q = ns.iter().map(|&n| (n, false)).collect::<VecDeque<_>>();
 
     let mut left_to_mix = len;
     let mut i = 0;
@@ -23,7 +27,7 @@ fn mix_numbers(ns: &Vec<i64>) -> Vec<i64> {
     while left_to_mix > 0 {
         let item = q[i];
 
-        if item.1 == true {
+        if item.1 {
             // Item was already moved once
             i += 1;
             continue;
@@ -42,10 +46,8 @@ fn mix_numbers(ns: &Vec<i64>) -> Vec<i64> {
     q.into_iter().map(|a| a.0).collect()
 }
 
-fn find_grove_coords(ns: &Vec<i64>) -> Option<(i64, i64, i64)> {
-    let Some(zero_idx) = ns.iter().position(|&n| n == 0) else {
-        return None;
-    };
+fn find_grove_coords(ns: &[i64]) -> Option<(i64, i64, i64)> {
+    let zero_idx = ns.iter().position(|&n| n == 0)?;
 
     Some((
         ns[(zero_idx + 1000) % ns.len()],
@@ -54,17 +56,17 @@ fn find_grove_coords(ns: &Vec<i64>) -> Option<(i64, i64, i64)> {
     ))
 }
 
-fn part_1(ns: &Vec<i64>) -> Result<i64, anyhow::Error> {
-    let mixed = mix_numbers(&ns);
+fn part_1(ns: &[i64]) -> Result<i64, anyhow::Error> {
+    let mixed = mix_numbers(ns);
     let grove_coords = find_grove_coords(&mixed)
         .ok_or_else(|| anyhow!("Couldn't find grove coordinates from '{:?}'", mixed))?;
 
     Ok(grove_coords.0 + grove_coords.1 + grove_coords.2)
 }
 
-fn part_2(ns: &Vec<i64>) -> Result<i64, anyhow::Error> {
+fn part_2(ns: &[i64]) -> Result<i64, anyhow::Error> {
     let decryption_key = 811589153;
-    let ns = ns.into_iter().map(|n| n * decryption_key).collect::<Vec<_>>();
+    let ns = ns.iter().map(|n| n * decryption_key).collect::<Vec<_>>();
 
     // TODO
     let mixed = ns;
@@ -80,8 +82,8 @@ fn main() -> Result<(), anyhow::Error> {
     let input_string = read_file_to_string(&input_file_path)?;
     let ns = parse_input(&input_string)?;
 
-    println!("Part 1 solution: {}", part_1(&ns)?);
-    println!("Part 2 solution: {}", part_2(&ns)?);
+    println!("Part 1 solution: {}", profile("part 1", || part_1(&ns))?);
+    println!("Part 2 solution: {}", profile("part 2", || part_2(&ns))?);
 
     Ok(())
 }
@@ -101,7 +103,7 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        let ns = parse_input(&TEST_INPUT).unwrap();
+        let ns = parse_input(TEST_INPUT).unwrap();
 
         assert_eq!(ns, vec![1, 2, -3, 3, -2, 0, 4]);
     }
@@ -113,7 +115,7 @@ mod tests {
 
     #[test]
     fn test_mix_numbers() {
-        let ns = parse_input(&TEST_INPUT).unwrap();
+        let ns = parse_input(TEST_INPUT).unwrap();
         let result = mix_numbers(&ns);
 
         assert_eq!(result, vec![1, 2, -3, 4, 0, 3, -2]);
@@ -129,7 +131,7 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let ns = parse_input(&TEST_INPUT).unwrap();
+        let ns = parse_input(TEST_INPUT).unwrap();
 
         assert_eq!(part_1(&ns).unwrap(), 3);
     }
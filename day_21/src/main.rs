@@ -1,7 +1,11 @@
 use std::{collections::HashMap, str::FromStr};
 
 use anyhow::{anyhow, bail, Context};
-use common::{get_arg, read_file_to_string};
+use common::{get_arg, profiling::profile, read_file_to_string};
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOCATOR: common::profiling::CountingAllocator = common::profiling::CountingAllocator;
 
 #[derive(Debug, PartialEq)]
 enum Monkey {
@@ -60,8 +64,8 @@ fn eval_monkey(monkey_name: &str, monkeys: &HashMap<String, Monkey>) -> Result<i
     match &monkeys[monkey_name] {
         Monkey::Value(v) => Ok(*v),
         Monkey::Operation(left, op, right) => {
-            let left = eval_monkey(&left, monkeys)?;
-            let right = eval_monkey(&right, monkeys)?;
+            let left = eval_monkey(left, monkeys)?;
+            let right = eval_monkey(right, monkeys)?;
 
             match op {
                 '+' => Ok(left + right),
@@ -79,7 +83,10 @@ fn main() -> Result<(), anyhow::Error> {
     let input_string = read_file_to_string(&input_file_path)?;
     let Problem { monkeys } = input_string.parse()?;
 
-    println!("Part 1 solution: {}", eval_monkey("root", &monkeys)?);
+    println!(
+        "Part 1 solution: {}",
+        profile("part 1", || eval_monkey("root", &monkeys))?
+    );
     println!("Part 2 solution: {}", 0);
 
     Ok(())
@@ -0,0 +1,159 @@
+//! Running solvers of all days concurrently, summarized in a single table.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use rayon::prelude::*;
+
+use crate::day::{self, format_duration, Day, Run, Status};
+
+/// Run of a day which couldn't even start, failing with `message`.
+fn failed(message: String) -> Run {
+    Run {
+        answers: vec![],
+        elapsed: Duration::ZERO,
+        status: Status::Error(message),
+    }
+}
+
+/// Runs solvers of all days on a pool of `jobs` threads, each on its default
+/// input and within `timeout`. Every solver runs in its own process, so a
+/// panicking (or hanging) day does not affect the rest, and neither does a day
+/// which fails to build or start.
+pub fn run_all(jobs: usize, timeout: Duration) -> Result<Vec<(Day, Run)>, anyhow::Error> {
+    let days = Day::all();
+    let binaries = day::build_many(&days)?;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .context("trying to create thread pool")?;
+
+    Ok(pool.install(|| {
+        days.par_iter()
+            .zip(binaries.par_iter())
+            .map(|(&day, binary)| {
+                let input = day.default_input();
+                let run = match binary {
+                    Err(errors) => failed(format!("build failed:\n{}", errors.trim_end())),
+                    Ok(_) if !input.exists() => failed(format!("missing {}", input.display())),
+                    Ok(binary) => day::run(binary, &input, Some(timeout))
+                        .unwrap_or_else(|e| failed(format!("{:#}", e))),
+                };
+
+                (day, run)
+            })
+            .collect()
+    }))
+}
+
+/// Renders table of day, part, answer, time and status, with one row per
+/// answer, followed by details of failed runs.
+pub fn render_summary(results: &[(Day, Run)]) -> String {
+    let mut rows = vec![[
+        "day".to_string(),
+        "part".to_string(),
+        "answer".to_string(),
+        "time".to_string(),
+        "status".to_string(),
+    ]];
+
+    for (day, run) in results {
+        let time = format_duration(run.elapsed);
+        let status = run.status.to_string();
+
+        if run.answers.is_empty() {
+            rows.push([
+                day.to_string(),
+                "-".to_string(),
+                "-".to_string(),
+                time.clone(),
+                status.clone(),
+            ]);
+        }
+
+        for (part, answer) in run.answers.iter().enumerate() {
+            rows.push([
+                day.to_string(),
+                (part + 1).to_string(),
+                answer.clone(),
+                time.clone(),
+                status.clone(),
+            ]);
+        }
+    }
+
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut summary: String = rows
+        .iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell))
+                .collect::<Vec<_>>()
+                .join("  ");
+
+            format!("{}\n", line.trim_end())
+        })
+        .collect();
+
+    for (day, run) in results {
+        if let Status::Error(message) | Status::Panicked(message) = &run.status {
+            summary.push_str(&format!("\n{} {}: {}", day, run.status, message));
+        }
+    }
+
+    let ok_count = results
+        .iter()
+        .filter(|(_, run)| run.status == Status::Ok)
+        .count();
+    summary.push_str(&format!("\n{}/{} days ok\n", ok_count, results.len()));
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(answers: &[&str], status: Status) -> Run {
+        Run {
+            answers: answers.iter().map(|a| a.to_string()).collect(),
+            elapsed: Duration::from_millis(2),
+            status,
+        }
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let results = vec![
+            ("1".parse().unwrap(), run(&["69626", "206780"], Status::Ok)),
+            (
+                "17".parse().unwrap(),
+                run(&[], Status::Panicked("unknown instruction".to_string())),
+            ),
+            ("19".parse().unwrap(), run(&["1294"], Status::Timeout)),
+        ];
+
+        assert_eq!(
+            render_summary(&results),
+            "\
+day     part  answer  time   status
+day_01  1     69626   2.0ms  ok
+day_01  2     206780  2.0ms  ok
+day_17  -     -       2.0ms  panicked
+day_19  1     1294    2.0ms  timeout
+
+day_17 panicked: unknown instruction
+1/3 days ok
+"
+        );
+    }
+}
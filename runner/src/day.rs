@@ -0,0 +1,455 @@
+//! Building, running and testing solvers of individual days, each of which
+//! lives in its own `day_NN` package of the workspace.
+
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Display,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Stdio},
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context};
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Day(u8);
+
+impl FromStr for Day {
+    type Err = anyhow::Error;
+
+    /// Accepts `7`, `07` and `day_07` forms.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let num: u8 = s
+            .trim_start_matches("day_")
+            .parse()
+            .with_context(|| format!("couldn't parse day number from '{}'", s))?;
+
+        if !(1..=25).contains(&num) {
+            bail!("day number out of range: {}", num);
+        }
+
+        Ok(Day(num))
+    }
+}
+
+impl Display for Day {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day_{:02}", self.0)
+    }
+}
+
+impl Day {
+    /// Returns all days that have a package in the workspace.
+    pub fn all() -> Vec<Day> {
+        (1..=25)
+            .map(Day)
+            .filter(|day| day.dir().join("Cargo.toml").exists())
+            .collect()
+    }
+
+    pub fn package(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        workspace_root().join(self.package())
+    }
+
+    pub fn default_input(&self) -> PathBuf {
+        self.dir().join("input.txt")
+    }
+}
+
+pub fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("runner package lives inside the workspace")
+        .to_path_buf()
+}
+
+fn cargo() -> Command {
+    let mut command = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    command.current_dir(workspace_root());
+
+    command
+}
+
+/// Returns last non-empty line of `stderr`, which usually is the most
+/// descriptive one.
+fn last_line(stderr: &[u8]) -> String {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Paths to binaries and compiler errors, by name of the package, found in
+/// `cargo build --message-format=json` output.
+#[derive(Debug, Default, PartialEq)]
+struct BuildOutput {
+    binaries: HashMap<String, PathBuf>,
+    errors: HashMap<String, Vec<String>>,
+}
+
+fn parse_build_output(stdout: &str) -> BuildOutput {
+    let mut output = BuildOutput::default();
+
+    for line in stdout.lines() {
+        // Cargo may interleave other output with JSON messages
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let Some(package) = message["target"]["name"].as_str() else {
+            continue;
+        };
+
+        match message["reason"].as_str() {
+            Some("compiler-artifact") => {
+                if let Some(executable) = message["executable"].as_str() {
+                    output
+                        .binaries
+                        .insert(package.to_string(), PathBuf::from(executable));
+                }
+            }
+            Some("compiler-message") if message["message"]["level"] == "error" => {
+                if let Some(rendered) = message["message"]["rendered"].as_str() {
+                    output
+                        .errors
+                        .entry(package.to_string())
+                        .or_default()
+                        .push(rendered.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    output
+}
+
+/// Builds binaries of `days` in release mode, returning path to each of them
+/// (wherever the target directory is configured to be), or compiler errors
+/// when it couldn't be built. Build keeps going past days which fail to
+/// compile, so they don't prevent the rest from being built.
+pub fn build_many(days: &[Day]) -> Result<Vec<Result<PathBuf, String>>, anyhow::Error> {
+    let mut command = cargo();
+    command.args([
+        "build",
+        "--release",
+        "--keep-going",
+        "--message-format=json",
+    ]);
+    for day in days {
+        command.args(["-p", &day.package()]);
+    }
+
+    let output = command.output().context("trying to run cargo build")?;
+    let BuildOutput {
+        mut binaries,
+        mut errors,
+    } = parse_build_output(&String::from_utf8_lossy(&output.stdout));
+
+    // Day without errors of its own most likely failed on one of its
+    // dependencies, or cargo itself failed before compiling anything
+    let packages: Vec<String> = days.iter().map(Day::package).collect();
+    let mut dependency_errors: String = errors
+        .iter()
+        .filter(|(package, _)| !packages.contains(package))
+        .flat_map(|(_, errors)| errors.iter().map(String::as_str))
+        .collect();
+    if dependency_errors.is_empty() {
+        dependency_errors = last_line(&output.stderr);
+    }
+
+    Ok(packages
+        .iter()
+        .map(|package| match binaries.remove(package) {
+            Some(binary) => Ok(binary),
+            None => Err(errors
+                .remove(package)
+                .map_or_else(|| dependency_errors.clone(), |errors| errors.concat())),
+        })
+        .collect())
+}
+
+/// Builds binary of `day` in release mode, returning path to it. On failure,
+/// error carries compiler errors.
+pub fn build(day: Day) -> Result<PathBuf, anyhow::Error> {
+    build_many(&[day])?
+        .remove(0)
+        .map_err(|errors| anyhow!("build failed:\n{}", errors))
+        .with_context(|| format!("building {}", day))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    Ok,
+    Error(String),
+    Panicked(String),
+    Timeout,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Ok => write!(f, "ok"),
+            Status::Error(_) => write!(f, "error"),
+            Status::Panicked(_) => write!(f, "panicked"),
+            Status::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Run {
+    /// Answers in order of parts, as printed by the solver
+    pub answers: Vec<String>,
+    pub elapsed: Duration,
+    pub status: Status,
+}
+
+/// Extracts answers from `Part N solution: X` lines of solver output, ignoring
+/// any other diagnostic output.
+pub fn parse_answers(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (_part, answer) = line.strip_prefix("Part ")?.split_once(" solution: ")?;
+
+            Some(answer.to_string())
+        })
+        .collect()
+}
+
+fn status_from_exit(exit_status: ExitStatus, stderr: &[u8]) -> Status {
+    if exit_status.success() {
+        return Status::Ok;
+    }
+
+    let stderr_string = String::from_utf8_lossy(stderr);
+    match stderr_string
+        .lines()
+        .find(|line| line.contains("panicked at"))
+    {
+        // Panic message follows the location line
+        Some(location) => {
+            let message = stderr_string
+                .lines()
+                .skip_while(|&line| line != location)
+                .nth(1)
+                .unwrap_or(location);
+
+            Status::Panicked(message.to_string())
+        }
+        None => Status::Error(last_line(stderr)),
+    }
+}
+
+fn read_to_end_in_background(
+    mut source: impl Read + Send + 'static,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = vec![];
+        // Partial output is still useful, so ignore read errors
+        let _ = source.read_to_end(&mut buf);
+
+        buf
+    })
+}
+
+/// Runs already built solver `binary` on `input`, killing it if it doesn't
+/// finish within `timeout`.
+pub fn run(binary: &Path, input: &Path, timeout: Option<Duration>) -> Result<Run, anyhow::Error> {
+    let start = Instant::now();
+    let mut child = Command::new(binary)
+        .arg(input)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("trying to run {}", binary.display()))?;
+
+    // Drain pipes concurrently, so the child never blocks on a full pipe
+    let stdout = read_to_end_in_background(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_to_end_in_background(child.stderr.take().expect("stderr is piped"));
+
+    let exit_status = loop {
+        if let Some(exit_status) = child.try_wait()? {
+            break Some(exit_status);
+        }
+
+        if timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+            child.kill()?;
+            child.wait()?;
+            break None;
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    };
+    let elapsed = start.elapsed();
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    Ok(Run {
+        answers: parse_answers(&String::from_utf8_lossy(&stdout)),
+        elapsed,
+        status: match exit_status {
+            Some(exit_status) => status_from_exit(exit_status, &stderr),
+            None => Status::Timeout,
+        },
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestSummary {
+    pub passed: usize,
+    pub failed: usize,
+    /// Time spent running tests, excluding their build
+    pub elapsed: Duration,
+}
+
+impl Display for TestSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.failed == 0 {
+            write!(f, "{} passed", self.passed)
+        } else {
+            write!(f, "{} passed, {} failed", self.passed, self.failed)
+        }
+    }
+}
+
+/// Sums up `test result: ...` lines of `cargo test` output.
+pub fn parse_test_summary(stdout: &str) -> Result<TestSummary, anyhow::Error> {
+    let mut summary = TestSummary {
+        passed: 0,
+        failed: 0,
+        elapsed: Duration::ZERO,
+    };
+    let mut found = false;
+
+    for line in stdout.lines() {
+        let Some(rest) = line.strip_prefix("test result: ") else {
+            continue;
+        };
+        found = true;
+
+        // Time has a decimal point, so it's split off before the counts
+        let (counts, elapsed) = rest.rsplit_once("; finished in ").unwrap_or((rest, ""));
+        if let Some(secs) = elapsed.strip_suffix('s') {
+            summary.elapsed += Duration::from_secs_f64(secs.parse()?);
+        }
+
+        for part in counts.split(['.', ';']) {
+            let Some((count, label)) = part.trim().split_once(' ') else {
+                continue;
+            };
+            match label {
+                "passed" => summary.passed += count.parse::<usize>()?,
+                "failed" => summary.failed += count.parse::<usize>()?,
+                _ => {}
+            }
+        }
+    }
+
+    if !found {
+        return Err(anyhow!("no test results in cargo test output"));
+    }
+
+    Ok(summary)
+}
+
+/// Runs tests of `day` in release mode (to share build artifacts with
+/// [`build`]).
+pub fn test(day: Day) -> Result<TestSummary, anyhow::Error> {
+    let output = cargo()
+        .args(["test", "--release", "-p", &day.package()])
+        .output()
+        .context("trying to run cargo test")?;
+
+    parse_test_summary(&String::from_utf8_lossy(&output.stdout))
+        .with_context(|| format!("running tests of {}: {}", day, last_line(&output.stderr)))
+}
+
+pub fn format_duration(d: Duration) -> String {
+    if d.as_secs() > 0 {
+        format!("{:.2}s", d.as_secs_f64())
+    } else {
+        format!("{:.1}ms", d.as_secs_f64() * 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_from_str() {
+        assert_eq!("7".parse::<Day>().unwrap(), Day(7));
+        assert_eq!("07".parse::<Day>().unwrap(), Day(7));
+        assert_eq!("day_21".parse::<Day>().unwrap(), Day(21));
+        assert!("0".parse::<Day>().is_err());
+        assert!("x".parse::<Day>().is_err());
+        assert_eq!(Day(7).package(), "day_07");
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let stdout = "\
+new best: 1600
+Part 1 solution: 1651
+done 0 iterations
+Part 2 solution: NTWZZWHFV";
+
+        assert_eq!(parse_answers(stdout), vec!["1651", "NTWZZWHFV"]);
+    }
+
+    #[test]
+    fn test_parse_build_output() {
+        let stdout = r#"{"reason":"compiler-artifact","target":{"kind":["lib"],"name":"common"},"executable":null}
+{"reason":"compiler-artifact","target":{"kind":["bin"],"name":"day_06"},"executable":"/tmp/target/release/day_06"}
+{"reason":"compiler-message","target":{"kind":["bin"],"name":"day_07"},"message":{"level":"warning","rendered":"warning: unused variable\n"}}
+{"reason":"compiler-message","target":{"kind":["bin"],"name":"day_07"},"message":{"level":"error","rendered":"error[E0308]: mismatched types\n"}}
+{"reason":"build-finished","success":false}"#;
+
+        assert_eq!(
+            parse_build_output(stdout),
+            BuildOutput {
+                binaries: HashMap::from([(
+                    "day_06".to_string(),
+                    PathBuf::from("/tmp/target/release/day_06")
+                )]),
+                errors: HashMap::from([(
+                    "day_07".to_string(),
+                    vec!["error[E0308]: mismatched types\n".to_string()]
+                )]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_test_summary() {
+        let stdout = "\
+running 3 tests
+test tests::a ... ok
+test result: ok. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.25s
+
+test result: ok. 4 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 1.50s";
+
+        assert_eq!(
+            parse_test_summary(stdout).unwrap(),
+            TestSummary {
+                passed: 6,
+                failed: 1,
+                elapsed: Duration::from_millis(1750),
+            }
+        );
+        assert!(parse_test_summary("error: could not compile").is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use std::{path::PathBuf, thread, time::Duration};
+
+use anyhow::{bail, Context};
+use common::{get_arg, get_option};
+
+mod all;
+mod day;
+mod watch;
+
+use day::Day;
+
+fn main() -> Result<(), anyhow::Error> {
+    let command = get_arg(1).context("pass command (`all` or `watch`) as first argument")?;
+
+    match command.as_str() {
+        "all" => {
+            let jobs = match get_option("--jobs") {
+                Some(jobs) => jobs.parse().context("couldn't parse --jobs")?,
+                None => thread::available_parallelism().map_or(1, |n| n.get()),
+            };
+            let timeout = match get_option("--timeout") {
+                Some(secs) => Duration::from_secs_f64(
+                    secs.parse().context("couldn't parse --timeout seconds")?,
+                ),
+                None => Duration::from_secs(60),
+            };
+
+            // Kept off stdout, which only holds the summary
+            eprintln!("Building {} days...", Day::all().len());
+            let results = all::run_all(jobs, timeout)?;
+            print!("{}", all::render_summary(&results));
+
+            Ok(())
+        }
+        "watch" => {
+            let day: Day = get_arg(2)
+                .context("pass day number as second argument")?
+                .parse()?;
+            let input = get_option("--input")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| day.default_input());
+            let interval = match get_option("--interval") {
+                Some(millis) => Duration::from_millis(
+                    millis
+                        .parse()
+                        .context("couldn't parse --interval milliseconds")?,
+                ),
+                None => Duration::from_millis(500),
+            };
+
+            watch::watch(day, &input, interval)
+        }
+        other => bail!("unknown command: {}", other),
+    }
+}
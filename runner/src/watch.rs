@@ -0,0 +1,235 @@
+//! Watch mode, re-running a day whenever its sources or input change.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::day::{self, format_duration, Day, Run, TestSummary};
+
+/// Modification times of watched files
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                collect_files(&entry.path(), files);
+            }
+        }
+        Err(_) => files.push(path.to_path_buf()),
+    }
+}
+
+fn take_snapshot(paths: &[PathBuf]) -> Snapshot {
+    let mut files = vec![];
+    for path in paths {
+        collect_files(path, &mut files);
+    }
+
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let modified = fs::metadata(&file).and_then(|m| m.modified()).ok()?;
+
+            Some((file, modified))
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+struct Outcome {
+    run: Run,
+    tests: Result<TestSummary, String>,
+}
+
+fn rebuild_and_run(day: Day, input: &Path) -> Result<Outcome, anyhow::Error> {
+    let binary = day::build(day)?;
+    let run = day::run(&binary, input, None)?;
+    let tests = day::test(day).map_err(|e| format!("{:#}", e));
+
+    Ok(Outcome { run, tests })
+}
+
+fn describe_run(run: Option<&Run>, part: usize) -> String {
+    match run {
+        Some(run) => run
+            .answers
+            .get(part)
+            .cloned()
+            .unwrap_or_else(|| "-".to_string()),
+        None => "-".to_string(),
+    }
+}
+
+/// Renders previous and new outcome side by side, including timings of both
+/// runs and tests.
+fn render_comparison(previous: Option<&Outcome>, new: &Outcome) -> String {
+    let previous_run = previous.map(|o| &o.run);
+    let parts_count = new
+        .run
+        .answers
+        .len()
+        .max(previous_run.map_or(0, |r| r.answers.len()));
+
+    let mut rows = vec![("".to_string(), "previous".to_string(), "new".to_string())];
+
+    for part in 0..parts_count {
+        rows.push((
+            format!("part {}", part + 1),
+            describe_run(previous_run, part),
+            describe_run(Some(&new.run), part),
+        ));
+    }
+
+    rows.push((
+        "time".to_string(),
+        previous_run.map_or("-".to_string(), |r| format_duration(r.elapsed)),
+        format_duration(new.run.elapsed),
+    ));
+    rows.push((
+        "status".to_string(),
+        previous_run.map_or("-".to_string(), |r| r.status.to_string()),
+        new.run.status.to_string(),
+    ));
+
+    let describe_tests = |tests: &Result<TestSummary, String>| match tests {
+        Ok(summary) => summary.to_string(),
+        Err(_) => "error".to_string(),
+    };
+    rows.push((
+        "tests".to_string(),
+        previous.map_or("-".to_string(), |o| describe_tests(&o.tests)),
+        describe_tests(&new.tests),
+    ));
+
+    let describe_test_time = |tests: &Result<TestSummary, String>| match tests {
+        Ok(summary) => format_duration(summary.elapsed),
+        Err(_) => "-".to_string(),
+    };
+    rows.push((
+        "test time".to_string(),
+        previous.map_or("-".to_string(), |o| describe_test_time(&o.tests)),
+        describe_test_time(&new.tests),
+    ));
+
+    let width = rows.iter().map(|(_, p, _)| p.len()).max().unwrap_or(0);
+
+    rows.into_iter()
+        .map(|(label, previous, new)| {
+            let marker = if label.starts_with("part") && previous != "-" && previous != new {
+                "  (changed)"
+            } else {
+                ""
+            };
+
+            format!("{:<9} {:<width$}   {}{}\n", label, previous, new, marker)
+        })
+        .collect()
+}
+
+/// Watches `src/` directory and manifest of `day`, as well as `input` file,
+/// polling for changes every `interval`. On change, rebuilds the solver, runs
+/// it and its tests, and prints answers side by side with previous ones.
+pub fn watch(day: Day, input: &Path, interval: Duration) -> Result<(), anyhow::Error> {
+    let watched = vec![
+        day.dir().join("src"),
+        day.dir().join("Cargo.toml"),
+        input.to_path_buf(),
+    ];
+
+    let mut previous: Option<Outcome> = None;
+    let mut last_snapshot: Option<Snapshot> = None;
+
+    println!("Watching {} with input {}", day, input.display());
+
+    loop {
+        let snapshot = take_snapshot(&watched);
+
+        if last_snapshot.as_ref() != Some(&snapshot) {
+            last_snapshot = Some(snapshot);
+
+            println!("\nChange detected, rebuilding {}...", day);
+
+            match rebuild_and_run(day, input) {
+                Ok(outcome) => {
+                    print!("{}", render_comparison(previous.as_ref(), &outcome));
+
+                    if let day::Status::Error(message) | day::Status::Panicked(message) =
+                        &outcome.run.status
+                    {
+                        println!("{}", message);
+                    }
+                    if let Err(message) = &outcome.tests {
+                        println!("{}", message);
+                    }
+
+                    previous = Some(outcome);
+                }
+                // Keep previous outcome around, so it's compared against once
+                // the build is fixed
+                Err(e) => println!("{:#}", e),
+            }
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day::Status;
+
+    fn outcome(answers: &[&str], millis: u64, test_millis: u64) -> Outcome {
+        Outcome {
+            run: Run {
+                answers: answers.iter().map(|a| a.to_string()).collect(),
+                elapsed: Duration::from_millis(millis),
+                status: Status::Ok,
+            },
+            tests: Ok(TestSummary {
+                passed: 3,
+                failed: 0,
+                elapsed: Duration::from_millis(test_millis),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_render_comparison() {
+        let previous = outcome(&["24000", "45000"], 12, 40);
+        let new = outcome(&["24000", "45001"], 3, 1500);
+
+        assert_eq!(
+            render_comparison(Some(&previous), &new),
+            "          previous   new
+part 1    24000      24000
+part 2    45000      45001  (changed)
+time      12.0ms     3.0ms
+status    ok         ok
+tests     3 passed   3 passed
+test time 40.0ms     1.50s
+"
+        );
+    }
+
+    #[test]
+    fn test_render_comparison_without_previous() {
+        let new = outcome(&["1"], 1, 2);
+
+        assert_eq!(
+            render_comparison(None, &new),
+            "          previous   new
+part 1    -          1
+time      -          1.0ms
+status    -          ok
+tests     -          3 passed
+test time -          2.0ms
+"
+        );
+    }
+}